@@ -0,0 +1,104 @@
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+// What a part of a problem produced. Integers that fit in an i64 are always
+// `Int`, so two answers with the same value compare equal however they were made.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Int(i64),
+    BigInt(i128),
+    Str(String),
+    List(Vec<Answer>),
+    Unsolved,
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{n}"),
+            Answer::BigInt(n) => write!(f, "{n}"),
+            Answer::Str(s) => write!(f, "{s}"),
+            // This is how AoC wants lists typed in
+            Answer::List(v) => write!(f, "{}", v.iter().join(",")),
+            Answer::Unsolved => write!(f, "Not Implemented"),
+        }
+    }
+}
+
+impl From<i128> for Answer {
+    fn from(n: i128) -> Self {
+        match i64::try_from(n) {
+            Ok(n) => Answer::Int(n),
+            Err(_) => Answer::BigInt(n),
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::from(n as i128)
+                }
+            }
+        )*
+    };
+}
+
+answer_from_int!(i32, i64, u8, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Str(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Str(s.to_string())
+    }
+}
+
+impl<T: Into<Answer>> From<Vec<T>> for Answer {
+    fn from(v: Vec<T>) -> Self {
+        Answer::List(v.into_iter().map(Into::into).collect())
+    }
+}
+
+// Whether every solved answer is the same; unsolved ones have no say
+pub fn all_agree<'a>(answers: impl IntoIterator<Item = &'a Answer>) -> bool {
+    answers
+        .into_iter()
+        .filter(|a| **a != Answer::Unsolved)
+        .all_equal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ints_are_canonical() {
+        assert_eq!(Answer::from(42usize), Answer::Int(42));
+        assert_eq!(Answer::from(42usize), Answer::from(42i64));
+        assert_eq!(Answer::from(u64::MAX), Answer::BigInt(u64::MAX as i128));
+        assert_eq!(Answer::from(7u8), Answer::Int(7));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Answer::from(vec![1, 5, 0]).to_string(), "1,5,0");
+        assert_eq!(Answer::from("hello").to_string(), "hello");
+        assert_eq!(Answer::from(u64::MAX).to_string(), u64::MAX.to_string());
+    }
+
+    #[test]
+    fn test_all_agree() {
+        let (a, b) = (Answer::from(3), Answer::from(4));
+        assert!(all_agree([&a, &Answer::Unsolved, &a]));
+        assert!(all_agree([&Answer::Unsolved]));
+        assert!(!all_agree([&a, &b]));
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    fmt::Display,
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::{Error, Result},
+    problem::{Part, ProblemFactory},
+};
+
+pub const DEFAULT_ITERATIONS: usize = 10;
+pub const DEFAULT_OUTPUT: &str = "bench.csv";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Parse,
+    Part(Part),
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Part(part) => write!(f, "part{part}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Summary {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Summary {
+    pub fn of(mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        Summary {
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Row {
+    pub name: String,
+    pub phase: Phase,
+    pub iterations: usize,
+    pub summary: Summary,
+}
+
+impl Display for Row {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<6} min {:>12?}  median {:>12?}  max {:>12?}",
+            self.phase, self.summary.min, self.summary.median, self.summary.max
+        )
+    }
+}
+
+fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let res = f();
+    (res, start.elapsed())
+}
+
+// Every iteration parses afresh, so parsing is sampled as often as the parts
+pub fn bench(
+    name: &str,
+    factory: ProblemFactory,
+    data: &str,
+    part: Option<Part>,
+    iterations: usize,
+) -> Result<Vec<Row>> {
+    let parts = Part::selected(part);
+
+    let mut samples = vec![vec![]; parts.len() + 1];
+    for _ in 0..iterations {
+        let (problem, elapsed) = time(|| factory(data));
+        let problem = problem?;
+        samples[0].push(elapsed);
+
+        for (i, p) in parts.iter().enumerate() {
+            let (res, elapsed) = time(|| match p {
+                Part::One => problem.prob1(),
+                Part::Two => problem.prob2(),
+            });
+            res.map_err(|e| e.in_part(*p))?;
+            samples[i + 1].push(elapsed);
+        }
+    }
+
+    let phases = std::iter::once(Phase::Parse).chain(parts.into_iter().map(Phase::Part));
+    Ok(phases
+        .zip(samples)
+        .map(|(phase, samples)| Row {
+            name: name.to_string(),
+            phase,
+            iterations,
+            summary: Summary::of(samples),
+        })
+        .collect())
+}
+
+// One line per problem and phase, in nanoseconds, so two runs diff cleanly
+pub fn to_csv(rows: &[Row]) -> String {
+    let mut csv = "name,phase,iterations,min_ns,median_ns,max_ns\n".to_string();
+    for row in rows {
+        csv += &format!(
+            "{},{},{},{},{},{}\n",
+            row.name,
+            row.phase,
+            row.iterations,
+            row.summary.min.as_nanos(),
+            row.summary.median.as_nanos(),
+            row.summary.max.as_nanos()
+        );
+    }
+    csv
+}
+
+pub fn save(path: &Path, rows: &[Row]) -> Result<()> {
+    fs::write(path, to_csv(rows))
+        .map_err(|e| Error::input(format!("could not write {}: {e}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let ms = Duration::from_millis;
+        assert_eq!(
+            Summary::of(vec![ms(5), ms(1), ms(3), ms(9)]),
+            Summary {
+                min: ms(1),
+                median: ms(5),
+                max: ms(9)
+            }
+        );
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let ns = Duration::from_nanos;
+        let rows = vec![Row {
+            name: "day1".to_string(),
+            phase: Phase::Part(Part::Two),
+            iterations: 3,
+            summary: Summary {
+                min: ns(1),
+                median: ns(2),
+                max: ns(3),
+            },
+        }];
+        assert_eq!(
+            to_csv(&rows),
+            "name,phase,iterations,min_ns,median_ns,max_ns\nday1,part2,3,1,2,3\n"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{
+    cell::Terrain,
+    grid::Grid,
+    point::TextPoint,
+    search::{bfs, Graph},
+};
+
+// Anywhere that isn't a wall, a step at a time, heading for `goal`
+pub struct OnFoot<'a> {
+    pub grid: &'a Grid<Terrain>,
+    pub goal: TextPoint,
+}
+
+impl OnFoot<'_> {
+    // Searches happily start from inside a wall, so check this first
+    pub fn open(&self, position: TextPoint) -> bool {
+        self.grid[position] != Terrain::Wall
+    }
+}
+
+impl Graph for OnFoot<'_> {
+    type State = TextPoint;
+
+    fn neighbours(&self, position: &TextPoint) -> impl Iterator<Item = (usize, TextPoint)> {
+        position
+            .neighbours4(self.grid.corner())
+            .filter_map(|(_, next)| self.open(next).then_some((1, next)))
+    }
+
+    // We can't do better than going straight there
+    fn heuristic(&self, position: &TextPoint) -> usize {
+        self.goal.row.abs_diff(position.row) + self.goal.col.abs_diff(position.col)
+    }
+}
+
+// When falling obstacles first cut `goal` off from `start`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Blockage {
+    // How many had fallen, counting the one that cut it off
+    pub fallen: usize,
+    pub blocker: TextPoint,
+    // A shortest way through from just before
+    pub last_path: Vec<TextPoint>,
+}
+
+// Obstacles fall one at a time onto an open map, in order. Once the way is
+// cut off it stays cut off, so we can binary search for when that happens
+// rather than trying after every one. None if it's never cut off, or there
+// wasn't a way to start with. Every obstacle has to be on the map.
+pub fn first_blocking(
+    width: usize,
+    height: usize,
+    obstacles: &[TextPoint],
+    start: TextPoint,
+    goal: TextPoint,
+) -> Option<Blockage> {
+    let path_after = |fallen: usize| {
+        let mut grid = Grid::filled(width, height, Terrain::Open);
+        for p in &obstacles[..fallen] {
+            grid[*p] = Terrain::Wall;
+        }
+        let on_foot = OnFoot { grid: &grid, goal };
+        if !on_foot.open(start) {
+            return None;
+        }
+        bfs(&on_foot, start, |p| *p == goal).path()
+    };
+
+    // There's a way after `lo` have fallen, but not after `hi`
+    let (mut lo, mut hi) = (0, obstacles.len());
+    if path_after(lo).is_none() || path_after(hi).is_some() {
+        return None;
+    }
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        if path_after(mid).is_some() {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    Some(Blockage {
+        fallen: hi,
+        blocker: obstacles[hi - 1],
+        last_path: path_after(lo)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_point(row: usize, col: usize) -> TextPoint {
+        TextPoint { row, col }
+    }
+
+    #[test]
+    fn test_first_blocking() {
+        let start = text_point(0, 0);
+        let goal = text_point(2, 2);
+        let obstacles = [
+            text_point(1, 1),
+            text_point(2, 0),
+            text_point(0, 2),
+            text_point(1, 2),
+        ];
+
+        assert_eq!(
+            first_blocking(3, 3, &obstacles, start, goal),
+            Some(Blockage {
+                fallen: 3,
+                blocker: text_point(0, 2),
+                last_path: vec![
+                    text_point(0, 0),
+                    text_point(0, 1),
+                    text_point(0, 2),
+                    text_point(1, 2),
+                    text_point(2, 2),
+                ],
+            })
+        );
+
+        // The middle on its own leaves both ways round
+        assert_eq!(first_blocking(3, 3, &obstacles[..1], start, goal), None);
+
+        // Landing on the start cuts it off as much as landing on the goal
+        for blocker in [start, goal] {
+            let fallen = [text_point(1, 1), blocker];
+            assert_eq!(
+                first_blocking(3, 3, &fallen, start, goal).map(|b| b.blocker),
+                Some(blocker)
+            );
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use crate::{
+    error::{Error, Result},
+    grid::Grid,
+    point::TextPoint,
+};
+
+// What a map is made of, each kind drawn with its own character
+pub trait Cell: Copy {
+    fn from_char(c: u8) -> Option<Self>;
+    fn to_char(self) -> u8;
+}
+
+// Declares a cell enum along with its characters, like
+//
+//     cells! {
+//         pub enum Terrain {
+//             Open = b'.',
+//             Wall = b'#',
+//         }
+//     }
+#[macro_export]
+macro_rules! cells {
+    ($(#[$meta:meta])* $vis:vis enum $name:ident { $($variant:ident = $c:literal),+ $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        $vis enum $name {
+            $($variant),+
+        }
+
+        impl $crate::cell::Cell for $name {
+            fn from_char(c: u8) -> Option<Self> {
+                match c {
+                    $($c => Some($name::$variant),)+
+                    _ => None,
+                }
+            }
+
+            fn to_char(self) -> u8 {
+                match self {
+                    $($name::$variant => $c),+
+                }
+            }
+        }
+    };
+}
+
+cells! {
+    // Most mazes are just somewhere you can go or somewhere you can't
+    pub enum Terrain {
+        Open = b'.',
+        Wall = b'#',
+    }
+}
+
+// Where each marker character was found before it was covered over
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Markers(HashMap<u8, Vec<TextPoint>>);
+
+impl Markers {
+    pub fn all(&self, marker: u8) -> &[TextPoint] {
+        self.0.get(&marker).map_or(&[], Vec::as_slice)
+    }
+
+    // The only place `marker` is, or None if it's missing or there's more than one
+    pub fn one(&self, marker: u8) -> Option<TextPoint> {
+        match self.all(marker) {
+            [p] => Some(*p),
+            _ => None,
+        }
+    }
+}
+
+impl<C: Cell> Grid<C> {
+    // Every character has to be a cell, or one of the `markers`. A marker is
+    // things like the start or a guard, standing on the cell it's paired with.
+    pub fn parse_cells(data: &str, markers: &[(u8, C)]) -> Result<(Self, Markers)> {
+        let chars = Grid::parse(data)?;
+        let lines: Vec<&str> = data.lines().collect();
+        let mut found = Markers::default();
+
+        let cells = chars
+            .cells()
+            .map(|(p, c)| {
+                if let Some((_, under)) = markers.iter().find(|(m, _)| m == c) {
+                    found.0.entry(*c).or_default().push(p);
+                    return Ok(*under);
+                }
+                C::from_char(*c).ok_or_else(|| {
+                    let line = lines[p.row];
+                    let text = line.get(p.col..p.col + 1).unwrap_or(line);
+                    Error::parse(
+                        data,
+                        text,
+                        format!("`{}` isn't part of the map", *c as char),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let grid = Grid::from_fn(chars.width(), chars.height(), |p| {
+            cells[p.row * chars.width() + p.col]
+        });
+        Ok((grid, found))
+    }
+
+    // Back to characters, for drawing
+    pub fn to_chars(&self) -> Grid<u8> {
+        self.map(|c| c.to_char())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn test_parse_cells() {
+        let data = "#S.\n.E#\n..S";
+        let (g, markers) =
+            Grid::parse_cells(data, &[(b'S', Terrain::Open), (b'E', Terrain::Open)]).unwrap();
+
+        assert_eq!(g[TextPoint { row: 0, col: 0 }], Terrain::Wall);
+        assert_eq!(g[TextPoint { row: 0, col: 1 }], Terrain::Open);
+        assert_eq!(markers.one(b'E'), Some(TextPoint { row: 1, col: 1 }));
+        assert_eq!(markers.all(b'S').len(), 2);
+        assert_eq!(markers.one(b'S'), None);
+        assert_eq!(markers.one(b'X'), None);
+        assert_eq!(g.to_chars(), Grid::parse("#..\n..#\n...").unwrap());
+    }
+
+    #[test]
+    fn test_parse_cells_bad_char() {
+        let e = Grid::<Terrain>::parse_cells("..\n.x", &[]).unwrap_err();
+        assert!(matches!(
+            e.kind,
+            ErrorKind::Parse {
+                line: 2,
+                column: 2,
+                ..
+            }
+        ));
+    }
+}
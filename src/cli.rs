@@ -0,0 +1,550 @@
+use std::{fmt::Display, path::PathBuf};
+
+use crate::{
+    bench::{DEFAULT_ITERATIONS, DEFAULT_OUTPUT},
+    input::InputSource,
+    problem::Part,
+    render::Format,
+    trace::{Level, DEFAULT_LEVEL},
+};
+
+pub const USAGE: &str = "Usage:
+    aoc2024 list                              List every registered problem
+    aoc2024 run <day> [--part 1|2]            Run one problem, optionally only one part
+    aoc2024 run --all [--part 1|2]            Run every registered problem
+    aoc2024 verify <day>|--all [--part 1|2]   Check answers against `data/answers.toml`
+    aoc2024 bench <day>|--all [--part 1|2]    Time parsing and each part
+    aoc2024 compare <day>|--all [--part 1|2]  Check a day's variants all get the same answers
+    aoc2024 <day>                             Shorthand for `run <day>`
+
+Options for `run`, `verify`, `bench` and `compare`:
+    --input <path>                            Read the puzzle input from <path> (`-` for stdin)
+    --data-dir <dir>                          Read `<dir>/dayN.txt` instead of `data/dayN.txt`
+
+Options for `run` and `verify`:
+    --example                                 Solve the examples from the puzzle text instead,
+                                              checking them with `verify` (`verify --examples` on
+                                              its own checks every day's)
+    --trace <day>[=<level>]                   Print what <day> is doing to stderr as it goes, at
+                                              `info`, `debug` (the default) or `trace`. Can be given
+                                              more than once.
+    --trace-format <format>                   Draw traced grids as `text` (the default), `ansi`
+                                              colours, or `ppm` or `png` images saved to
+                                              `trace-N.<format>` in the current directory
+
+Options for `verify`:
+    --answers <path>                          Read expected answers from <path> instead of
+                                              `answers.toml` in the data directory
+
+Options for `bench`:
+    --iterations <n>                          Run everything <n> times (default 10)
+    --output <path>                           Save the timings as CSV to <path> (default
+                                              `bench.csv`)";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Target {
+    Day(String),
+    All,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    List,
+    Help,
+    Run {
+        target: Target,
+        part: Option<Part>,
+        input: InputSource,
+        examples: bool,
+        trace: Vec<(String, Level)>,
+        trace_format: Format,
+    },
+    Verify {
+        target: Target,
+        part: Option<Part>,
+        input: InputSource,
+        answers: Option<PathBuf>,
+        examples: bool,
+        trace: Vec<(String, Level)>,
+        trace_format: Format,
+    },
+    Bench {
+        target: Target,
+        part: Option<Part>,
+        input: InputSource,
+        iterations: usize,
+        output: PathBuf,
+    },
+    Compare {
+        target: Target,
+        part: Option<Part>,
+        input: InputSource,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CliError {
+    MissingCommand,
+    UnknownCommand(String),
+    UnknownFlag(String),
+    MissingValue(&'static str),
+    InvalidPart(String),
+    InvalidIterations(String),
+    InvalidTraceLevel(String),
+    InvalidTraceFormat(String),
+    MissingDay,
+    UnexpectedArgument(String),
+    InputWithAll,
+    InputWithExamples,
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::MissingCommand => write!(f, "no command given"),
+            CliError::UnknownCommand(c) => write!(f, "unknown command `{c}`"),
+            CliError::UnknownFlag(flag) => write!(f, "unknown flag `{flag}`"),
+            CliError::MissingValue(flag) => write!(f, "`{flag}` needs a value"),
+            CliError::InvalidPart(p) => write!(f, "part must be 1 or 2, not `{p}`"),
+            CliError::InvalidIterations(n) => {
+                write!(f, "iterations must be a number above 0, not `{n}`")
+            }
+            CliError::InvalidTraceLevel(l) => {
+                write!(f, "trace level must be info, debug or trace, not `{l}`")
+            }
+            CliError::InvalidTraceFormat(t) => {
+                write!(f, "trace format must be text, ansi, ppm or png, not `{t}`")
+            }
+            CliError::MissingDay => write!(f, "expected a day or `--all`"),
+            CliError::UnexpectedArgument(a) => write!(f, "unexpected argument `{a}`"),
+            CliError::InputWithAll => {
+                write!(
+                    f,
+                    "`--input` names one file, so it can't be used with `--all`"
+                )
+            }
+            CliError::InputWithExamples => {
+                write!(
+                    f,
+                    "the examples are built in, so `--input` can't be used with them"
+                )
+            }
+        }
+    }
+}
+
+fn parse_part(s: &str) -> Result<Part, CliError> {
+    match s {
+        "1" => Ok(Part::One),
+        "2" => Ok(Part::Two),
+        _ => Err(CliError::InvalidPart(s.to_string())),
+    }
+}
+
+// `day16` or `day16=trace`
+fn parse_trace(s: &str) -> Result<(String, Level), CliError> {
+    match s.split_once('=') {
+        None => Ok((s.to_string(), DEFAULT_LEVEL)),
+        Some((day, level)) => Level::parse(level)
+            .map(|level| (day.to_string(), level))
+            .ok_or_else(|| CliError::InvalidTraceLevel(level.to_string())),
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Mode {
+    Run,
+    Verify,
+    Bench,
+    Compare,
+}
+
+// `run`, `verify`, `bench` and `compare` pick problems the same way, some just
+// have a few flags of their own
+fn parse_run(args: &mut impl Iterator<Item = String>, mode: Mode) -> Result<Command, CliError> {
+    let mut target = None;
+    let mut part = None;
+    let mut input = InputSource::default();
+    let mut answers = None;
+    let mut examples = false;
+    let mut trace = vec![];
+    let mut trace_format = Format::Text;
+    let mut iterations = DEFAULT_ITERATIONS;
+    let mut output = PathBuf::from(DEFAULT_OUTPUT);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" => target = Some(Target::All),
+            "--part" => {
+                let p = args.next().ok_or(CliError::MissingValue("--part"))?;
+                part = Some(parse_part(&p)?);
+            }
+            "--input" => {
+                let path = args.next().ok_or(CliError::MissingValue("--input"))?;
+                input = InputSource::from_arg(&path);
+            }
+            "--data-dir" => {
+                let dir = args.next().ok_or(CliError::MissingValue("--data-dir"))?;
+                input = InputSource::DataDir(PathBuf::from(dir));
+            }
+            "--example" | "--examples" if matches!(mode, Mode::Run | Mode::Verify) => {
+                examples = true;
+            }
+            "--trace" if matches!(mode, Mode::Run | Mode::Verify) => {
+                let day = args.next().ok_or(CliError::MissingValue("--trace"))?;
+                trace.push(parse_trace(&day)?);
+            }
+            "--trace-format" if matches!(mode, Mode::Run | Mode::Verify) => {
+                let t = args
+                    .next()
+                    .ok_or(CliError::MissingValue("--trace-format"))?;
+                trace_format = Format::parse(&t).ok_or(CliError::InvalidTraceFormat(t))?;
+            }
+            "--answers" if mode == Mode::Verify => {
+                let path = args.next().ok_or(CliError::MissingValue("--answers"))?;
+                answers = Some(PathBuf::from(path));
+            }
+            "--iterations" if mode == Mode::Bench => {
+                let n = args.next().ok_or(CliError::MissingValue("--iterations"))?;
+                iterations = match n.parse() {
+                    Ok(0) | Err(_) => return Err(CliError::InvalidIterations(n)),
+                    Ok(n) => n,
+                };
+            }
+            "--output" if mode == Mode::Bench => {
+                let path = args.next().ok_or(CliError::MissingValue("--output"))?;
+                output = PathBuf::from(path);
+            }
+            flag if flag.starts_with("--") => return Err(CliError::UnknownFlag(arg)),
+            _ if target.is_none() => target = Some(Target::Day(arg)),
+            _ => return Err(CliError::UnexpectedArgument(arg)),
+        }
+    }
+
+    let target = match target {
+        Some(target) => target,
+        None if examples && mode == Mode::Verify => Target::All,
+        None => return Err(CliError::MissingDay),
+    };
+    let single_file = matches!(input, InputSource::File(_) | InputSource::Stdin);
+    if target == Target::All && single_file {
+        return Err(CliError::InputWithAll);
+    }
+    if examples && single_file {
+        return Err(CliError::InputWithExamples);
+    }
+
+    Ok(match mode {
+        Mode::Run => Command::Run {
+            target,
+            part,
+            input,
+            examples,
+            trace,
+            trace_format,
+        },
+        Mode::Verify => Command::Verify {
+            target,
+            part,
+            input,
+            answers,
+            examples,
+            trace,
+            trace_format,
+        },
+        Mode::Bench => Command::Bench {
+            target,
+            part,
+            input,
+            iterations,
+            output,
+        },
+        Mode::Compare => Command::Compare {
+            target,
+            part,
+            input,
+        },
+    })
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, CliError> {
+    let mut args = args.into_iter();
+
+    let command = args.next().ok_or(CliError::MissingCommand)?;
+    match command.as_str() {
+        "list" => match args.next() {
+            None => Ok(Command::List),
+            Some(arg) => Err(CliError::UnexpectedArgument(arg)),
+        },
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        "run" => parse_run(&mut args, Mode::Run),
+        "verify" => parse_run(&mut args, Mode::Verify),
+        "bench" => parse_run(&mut args, Mode::Bench),
+        "compare" => parse_run(&mut args, Mode::Compare),
+        // Keep the original `cargo run -- day5` working
+        day if day.starts_with("day") => {
+            parse_run(&mut std::iter::once(command.clone()).chain(args), Mode::Run)
+        }
+        _ => Err(CliError::UnknownCommand(command)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Command, CliError> {
+        parse_args(s.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_parse_run() {
+        assert_eq!(
+            parse("run day5"),
+            Ok(Command::Run {
+                target: Target::Day("day5".to_string()),
+                part: None,
+                input: InputSource::default(),
+                examples: false,
+                trace: vec![],
+                trace_format: Format::Text,
+            })
+        );
+        assert_eq!(
+            parse("run day5 --part 2"),
+            Ok(Command::Run {
+                target: Target::Day("day5".to_string()),
+                part: Some(Part::Two),
+                input: InputSource::default(),
+                examples: false,
+                trace: vec![],
+                trace_format: Format::Text,
+            })
+        );
+        assert_eq!(
+            parse("run --part 1 --all"),
+            Ok(Command::Run {
+                target: Target::All,
+                part: Some(Part::One),
+                input: InputSource::default(),
+                examples: false,
+                trace: vec![],
+                trace_format: Format::Text,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_input() {
+        assert_eq!(
+            parse("run day1 --input -"),
+            Ok(Command::Run {
+                target: Target::Day("day1".to_string()),
+                part: None,
+                input: InputSource::Stdin,
+                examples: false,
+                trace: vec![],
+                trace_format: Format::Text,
+            })
+        );
+        assert_eq!(
+            parse("run day1 --input foo.txt"),
+            Ok(Command::Run {
+                target: Target::Day("day1".to_string()),
+                part: None,
+                input: InputSource::File(PathBuf::from("foo.txt")),
+                examples: false,
+                trace: vec![],
+                trace_format: Format::Text,
+            })
+        );
+        assert_eq!(
+            parse("run --all --data-dir inputs"),
+            Ok(Command::Run {
+                target: Target::All,
+                part: None,
+                input: InputSource::DataDir(PathBuf::from("inputs")),
+                examples: false,
+                trace: vec![],
+                trace_format: Format::Text,
+            })
+        );
+        assert_eq!(parse("run --all --input -"), Err(CliError::InputWithAll));
+    }
+
+    #[test]
+    fn test_parse_verify() {
+        assert_eq!(
+            parse("verify --all --part 2"),
+            Ok(Command::Verify {
+                target: Target::All,
+                part: Some(Part::Two),
+                input: InputSource::default(),
+                answers: None,
+                examples: false,
+                trace: vec![],
+                trace_format: Format::Text,
+            })
+        );
+        assert_eq!(
+            parse("verify day5 --answers mine.toml"),
+            Ok(Command::Verify {
+                target: Target::Day("day5".to_string()),
+                part: None,
+                input: InputSource::default(),
+                answers: Some(PathBuf::from("mine.toml")),
+                examples: false,
+                trace: vec![],
+                trace_format: Format::Text,
+            })
+        );
+        assert_eq!(
+            parse("run day5 --answers mine.toml"),
+            Err(CliError::UnknownFlag("--answers".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        assert_eq!(
+            parse("bench --all"),
+            Ok(Command::Bench {
+                target: Target::All,
+                part: None,
+                input: InputSource::default(),
+                iterations: DEFAULT_ITERATIONS,
+                output: PathBuf::from(DEFAULT_OUTPUT),
+            })
+        );
+        assert_eq!(
+            parse("bench day7 --part 1 --iterations 3 --output day7.csv"),
+            Ok(Command::Bench {
+                target: Target::Day("day7".to_string()),
+                part: Some(Part::One),
+                input: InputSource::default(),
+                iterations: 3,
+                output: PathBuf::from("day7.csv"),
+            })
+        );
+        assert_eq!(
+            parse("bench day7 --iterations 0"),
+            Err(CliError::InvalidIterations("0".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_compare() {
+        assert_eq!(
+            parse("compare day3 --part 2"),
+            Ok(Command::Compare {
+                target: Target::Day("day3".to_string()),
+                part: Some(Part::Two),
+                input: InputSource::default(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_examples() {
+        assert_eq!(
+            parse("run day4 --example"),
+            Ok(Command::Run {
+                target: Target::Day("day4".to_string()),
+                part: None,
+                input: InputSource::default(),
+                examples: true,
+                trace: vec![],
+                trace_format: Format::Text,
+            })
+        );
+        assert_eq!(
+            parse("verify --examples"),
+            Ok(Command::Verify {
+                target: Target::All,
+                part: None,
+                input: InputSource::default(),
+                answers: None,
+                examples: true,
+                trace: vec![],
+                trace_format: Format::Text,
+            })
+        );
+        assert_eq!(
+            parse("run day4 --example --input -"),
+            Err(CliError::InputWithExamples)
+        );
+        assert_eq!(
+            parse("bench day4 --example"),
+            Err(CliError::UnknownFlag("--example".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_trace() {
+        assert_eq!(
+            parse("run day16 --trace day16 --trace day18=trace"),
+            Ok(Command::Run {
+                target: Target::Day("day16".to_string()),
+                part: None,
+                input: InputSource::default(),
+                examples: false,
+                trace: vec![
+                    ("day16".to_string(), Level::Debug),
+                    ("day18".to_string(), Level::Trace),
+                ],
+                trace_format: Format::Text,
+            })
+        );
+        assert_eq!(
+            parse("run day16 --trace day16 --trace-format png"),
+            Ok(Command::Run {
+                target: Target::Day("day16".to_string()),
+                part: None,
+                input: InputSource::default(),
+                examples: false,
+                trace: vec![("day16".to_string(), Level::Debug)],
+                trace_format: Format::Png,
+            })
+        );
+        assert_eq!(
+            parse("run day16 --trace-format gif"),
+            Err(CliError::InvalidTraceFormat("gif".to_string()))
+        );
+        assert_eq!(
+            parse("verify day16 --trace day16=loud"),
+            Err(CliError::InvalidTraceLevel("loud".to_string()))
+        );
+        assert_eq!(
+            parse("bench day16 --trace day16"),
+            Err(CliError::UnknownFlag("--trace".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_shorthand() {
+        assert_eq!(parse("day5"), parse("run day5"));
+        assert_eq!(parse("list"), Ok(Command::List));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse(""), Err(CliError::MissingCommand));
+        assert_eq!(parse("run"), Err(CliError::MissingDay));
+        assert_eq!(
+            parse("run day5 --part 3"),
+            Err(CliError::InvalidPart("3".to_string()))
+        );
+        assert_eq!(
+            parse("run day5 --part"),
+            Err(CliError::MissingValue("--part"))
+        );
+        assert_eq!(
+            parse("frobnicate"),
+            Err(CliError::UnknownCommand("frobnicate".to_string()))
+        );
+        assert_eq!(
+            parse("run day5 day6"),
+            Err(CliError::UnexpectedArgument("day6".to_string()))
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use crate::point::TextPoint;
+
+// Like `TextPoint` but signed, for worlds that go off the top or left of the page
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Coord {
+    pub row: i64,
+    pub col: i64,
+}
+
+// The difference between two `Coord`s, or how far something moves in one go
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Vector {
+    pub row: i64,
+    pub col: i64,
+}
+
+impl Coord {
+    pub const fn new(row: i64, col: i64) -> Self {
+        Coord { row, col }
+    }
+
+    // Back onto a grid, if it lands on one with this bottom right `corner`
+    pub fn within(self, corner: TextPoint) -> Option<TextPoint> {
+        let p = TextPoint::try_from(self).ok()?;
+        (p.row <= corner.row && p.col <= corner.col).then_some(p)
+    }
+}
+
+impl Vector {
+    pub const fn new(row: i64, col: i64) -> Self {
+        Vector { row, col }
+    }
+}
+
+impl From<TextPoint> for Coord {
+    fn from(p: TextPoint) -> Self {
+        Coord::new(p.row as i64, p.col as i64)
+    }
+}
+
+impl TryFrom<Coord> for TextPoint {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(c: Coord) -> Result<Self, Self::Error> {
+        Ok(TextPoint {
+            row: c.row.try_into()?,
+            col: c.col.try_into()?,
+        })
+    }
+}
+
+impl Add<Vector> for Coord {
+    type Output = Coord;
+
+    fn add(self, v: Vector) -> Coord {
+        Coord::new(self.row + v.row, self.col + v.col)
+    }
+}
+
+impl AddAssign<Vector> for Coord {
+    fn add_assign(&mut self, v: Vector) {
+        *self = *self + v;
+    }
+}
+
+impl Sub<Vector> for Coord {
+    type Output = Coord;
+
+    fn sub(self, v: Vector) -> Coord {
+        self + -v
+    }
+}
+
+impl SubAssign<Vector> for Coord {
+    fn sub_assign(&mut self, v: Vector) {
+        *self = *self - v;
+    }
+}
+
+impl Sub for Coord {
+    type Output = Vector;
+
+    fn sub(self, other: Coord) -> Vector {
+        Vector::new(self.row - other.row, self.col - other.col)
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Vector {
+        Vector::new(self.row + other.row, self.col + other.col)
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, other: Vector) -> Vector {
+        self + -other
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.row, -self.col)
+    }
+}
+
+impl Mul<i64> for Vector {
+    type Output = Vector;
+
+    fn mul(self, n: i64) -> Vector {
+        Vector::new(self.row * n, self.col * n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::direction::Cardinal;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Coord::new(1, 2);
+        let b = Coord::new(4, 0);
+
+        assert_eq!(b - a, Vector::new(3, -2));
+        assert_eq!(a + (b - a), b);
+        assert_eq!(b - (b - a), a);
+        assert_eq!(-(b - a) * 2, Vector::new(-6, 4));
+
+        let mut c = a;
+        c += Cardinal::N.delta();
+        c += Cardinal::N.delta();
+        assert_eq!(c, Coord::new(-1, 2));
+    }
+
+    #[test]
+    fn test_text_points() {
+        let corner = TextPoint { row: 3, col: 3 };
+
+        assert_eq!(
+            Coord::new(2, 3).within(corner),
+            Some(TextPoint { row: 2, col: 3 })
+        );
+        assert_eq!(Coord::new(-1, 3).within(corner), None);
+        assert_eq!(Coord::new(2, 4).within(corner), None);
+        assert_eq!(Coord::from(TextPoint { row: 5, col: 6 }), Coord::new(5, 6));
+    }
+}
@@ -1,20 +1,29 @@
-use std::{collections::HashMap, fs};
+use std::collections::HashMap;
 
 use linkme::distributed_slice;
 use sorted_vec::SortedVec;
 
-use crate::{problem::Problem, PROBLEMS};
+use crate::{
+    answer::Answer,
+    error::{parse_at, Error, Result},
+    examples::{Example, EXAMPLES},
+    problem::{factory, Problem, ProblemFactory},
+    PROBLEMS,
+};
 
-fn deserialize(s: &str) -> (SortedVec<i64>, SortedVec<i64>) {
+fn deserialize(s: &str) -> Result<(SortedVec<i64>, SortedVec<i64>)> {
     let mut left = SortedVec::new();
     let mut right = SortedVec::new();
     for line in s.lines() {
         let mut ns = line.split_whitespace();
 
-        left.push(ns.next().unwrap().parse().unwrap());
-        right.push(ns.next().unwrap().parse().unwrap());
+        let (Some(l), Some(r)) = (ns.next(), ns.next()) else {
+            return Err(Error::parse(s, line, "expected two numbers"));
+        };
+        left.push(parse_at(s, l)?);
+        right.push(parse_at(s, r)?);
     }
-    return (left, right);
+    return Ok((left, right));
 }
 
 fn count_distance(left: &[i64], right: &[i64]) -> i64 {
@@ -41,51 +50,63 @@ pub struct Day1 {
 }
 
 impl Day1 {
-    pub fn new() -> Self {
-        let data = fs::read_to_string("data/day1.txt").unwrap();
-        let (left, right) = deserialize(&data);
-        Day1 { left, right }
+    pub fn with_data(data: &str) -> Result<Self> {
+        let (left, right) = deserialize(data)?;
+        Ok(Day1 { left, right })
     }
-    fn prob1_inner(&mut self) -> i64 {
+    fn prob1_inner(&self) -> i64 {
         count_distance(&self.left, &self.right)
     }
-    fn prob2_inner(&mut self) -> usize {
+    fn prob2_inner(&self) -> usize {
         let f = frequency(&self.right);
         multiply_frequency(&self.left, f)
     }
 }
 
 impl Problem for Day1 {
-    fn prob1(&mut self) -> Box<dyn std::fmt::Display> {
-        Box::new(self.prob1_inner())
+    fn parse(data: &str) -> Result<Self> {
+        Day1::with_data(data)
+    }
+
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
     }
 
-    fn prob2(&mut self) -> Box<dyn std::fmt::Display> {
-        Box::new(self.prob2_inner())
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner().into())
     }
 }
 
 #[distributed_slice(PROBLEMS)]
-fn register_day(p: &mut HashMap<String, fn() -> Box<dyn Problem>>) {
-    p.insert("day1".to_owned(), || Box::new(Day1::new()));
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day1".to_owned(), factory::<Day1>);
 }
 
-#[cfg(test)]
-mod tests {
-    use std::collections::HashMap;
-
-    use crate::day1::{count_distance, deserialize, frequency, Day1};
-
-    const TEST_DATA: &str = "3   4
+const TEST_DATA: &str = "3   4
             4   3
             2   5
             1   3
             3   9
             3   3";
 
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day1",
+    input: TEST_DATA,
+    part1: Some("11"),
+    part2: Some("31"),
+    factory: None,
+};
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs};
+
+    use crate::day1::{count_distance, deserialize, frequency, Day1, TEST_DATA};
+
     #[test]
     fn test_basic_decode() {
-        let (left, right) = deserialize(TEST_DATA);
+        let (left, right) = deserialize(TEST_DATA).unwrap();
 
         assert_eq!(left.as_slice(), vec![1, 2, 3, 3, 3, 4]);
         assert_eq!(right.as_slice(), vec![3, 3, 3, 4, 5, 9]);
@@ -93,15 +114,30 @@ mod tests {
 
     #[test]
     fn test_basic_distance() {
-        let (left, right) = deserialize(TEST_DATA);
+        let (left, right) = deserialize(TEST_DATA).unwrap();
 
         let res = count_distance(&left, &right);
         assert_eq!(res, 11);
     }
 
+    #[test]
+    fn test_bad_input() {
+        let e = deserialize("1 2\n3 b\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "could not parse input at line 2, column 3 (`b`): invalid digit found in string"
+        );
+
+        let e = deserialize("1 2\n3\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "could not parse input at line 2, column 1 (`3`): expected two numbers"
+        );
+    }
+
     #[test]
     fn test_frequency() {
-        let (_, right) = deserialize(TEST_DATA);
+        let (_, right) = deserialize(TEST_DATA).unwrap();
 
         let f = frequency(&right);
 
@@ -110,15 +146,16 @@ mod tests {
 
     #[test]
     fn test_problem_2_test() {
-        let (left, right) = deserialize(TEST_DATA);
-        let mut day1 = Day1 { left, right };
+        let (left, right) = deserialize(TEST_DATA).unwrap();
+        let day1 = Day1 { left, right };
         let res = day1.prob2_inner();
         assert_eq!(res, 31);
     }
 
     #[test]
     fn test_problem_2() {
-        let mut day1 = Day1::new();
+        let data = fs::read_to_string("data/day1.txt").unwrap();
+        let day1 = Day1::with_data(&data).unwrap();
 
         let res = day1.prob1_inner();
         assert_eq!(res, 2000468);
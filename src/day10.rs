@@ -1,121 +1,101 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
+
+use linkme::distributed_slice;
 
 use crate::{
-    day4::{explode_point, find_char_in_puzzle, TextPoint},
-    day6::{explode_point_with_directions, Direction},
+    answer::Answer,
+    error::Result,
+    examples::{Example, EXAMPLES},
+    grid::Grid,
+    point::TextPoint,
+    problem::{factory, Problem, ProblemFactory, PROBLEMS},
+    walk::Walker,
 };
 
-fn climb_trail(map: &Vec<Vec<u8>>, start: TextPoint) -> HashSet<(TextPoint, TextPoint)> {
-    let corner = TextPoint {
-        row: map.len() - 1,
-        col: map[0].len() - 1,
-    };
-
-    let mut s = vec![start];
+type Map = Grid<Option<u8>>;
 
-    let mut routes = HashSet::new();
+// Anything that isn't a digit is somewhere you can't stand
+fn heights(data: &str) -> Result<Map> {
+    Ok(Grid::parse(data)?.map(|c| c.is_ascii_digit().then(|| c - b'0')))
+}
 
-    while let Some(p) = s.pop() {
-        let c = map[p.row][p.col];
-        if c == b'9' {
-            routes.insert((start, p));
-            continue;
-        }
+// Trails only ever go up, one at a time
+fn trail_walker(map: &Map) -> Walker<'_, Option<u8>, impl Fn(&Option<u8>, &Option<u8>) -> bool> {
+    Walker::new(
+        map,
+        |from, to| matches!((from, to), (Some(a), Some(b)) if *b == a + 1),
+    )
+}
 
-        let all_moves: Vec<_> = explode_point_with_directions(p, corner, 2)
-            .into_iter()
-            .filter(|(d, v)| {
-                (v.len() == 2)
-                    && (*d == Direction::N
-                        || *d == Direction::E
-                        || *d == Direction::S
-                        || *d == Direction::W)
-            })
-            .collect();
-
-        for (_, maybe_next) in all_moves {
-            println!("Considering {maybe_next:?}");
-            let maybe_next_p = maybe_next[1];
-            let maybe_next_c = map[maybe_next_p.row][maybe_next_p.col];
-            if maybe_next_c.saturating_sub(c) == 1 {
-                println!("Adding {maybe_next_p:?}");
-                s.push(maybe_next_p);
-            }
-        }
-    }
+// How many 9s each trailhead can reach
+fn find_unique_trails(map: &Map) -> Vec<(TextPoint, usize)> {
+    let walker = trail_walker(map);
 
-    routes
+    map.find(&Some(0))
+        .into_iter()
+        .map(|p| {
+            let peaks = walker
+                .reachable(p)
+                .into_iter()
+                .filter(|q| map[*q] == Some(9));
+            (p, peaks.count())
+        })
+        .collect()
 }
 
-fn climb_all_trail(map: &Vec<Vec<u8>>, start: TextPoint) -> Vec<(TextPoint, TextPoint)> {
-    let corner = TextPoint {
-        row: map.len() - 1,
-        col: map[0].len() - 1,
-    };
+// How many different ways up there are from each trailhead
+fn find_all_trails(map: &Map) -> Vec<(TextPoint, usize)> {
+    let counts = trail_walker(map).path_counts(|p| map[p] == Some(9));
 
-    let mut s = vec![start];
+    map.find(&Some(0))
+        .into_iter()
+        .map(|p| (p, counts[p]))
+        .collect()
+}
 
-    let mut routes = Vec::new();
+pub struct Day10 {
+    map: Map,
+}
 
-    while let Some(p) = s.pop() {
-        let c = map[p.row][p.col];
-        if c == b'9' {
-            routes.push((start, p));
-            continue;
-        }
+impl Day10 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        Ok(Day10 {
+            map: heights(data)?,
+        })
+    }
 
-        let all_moves: Vec<_> = explode_point_with_directions(p, corner, 2)
+    fn prob1_inner(&self) -> usize {
+        find_unique_trails(&self.map)
             .into_iter()
-            .filter(|(d, v)| {
-                (v.len() == 2)
-                    && (*d == Direction::N
-                        || *d == Direction::E
-                        || *d == Direction::S
-                        || *d == Direction::W)
-            })
-            .collect();
-
-        for (_, maybe_next) in all_moves {
-            println!("Considering {maybe_next:?}");
-            let maybe_next_p = maybe_next[1];
-            let maybe_next_c = map[maybe_next_p.row][maybe_next_p.col];
-            if maybe_next_c.saturating_sub(c) == 1 {
-                println!("Adding {maybe_next_p:?}");
-                s.push(maybe_next_p);
-            }
-        }
+            .map(|(_, n)| n)
+            .sum()
     }
 
-    routes
+    fn prob2_inner(&self) -> usize {
+        find_all_trails(&self.map).into_iter().map(|(_, n)| n).sum()
+    }
 }
 
-fn find_unique_trails(map: &Vec<Vec<u8>>) -> Vec<(TextPoint, usize)> {
-    let zeros = find_char_in_puzzle(&map, b'0');
-
-    zeros
-        .into_iter()
-        .map(|p| (p, climb_trail(&map, p).len()))
-        .collect()
-}
+impl Problem for Day10 {
+    fn parse(data: &str) -> Result<Self> {
+        Day10::with_data(data)
+    }
 
-fn find_all_trails(map: &Vec<Vec<u8>>) -> Vec<(TextPoint, usize)> {
-    let zeros = find_char_in_puzzle(&map, b'0');
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
+    }
 
-    zeros
-        .into_iter()
-        .map(|p| (p, climb_all_trail(&map, p).len()))
-        .collect()
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner().into())
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use std::{collections::HashMap, fs};
-
-    use crate::day4::char_matrix;
-
-    use super::*;
+#[distributed_slice(PROBLEMS)]
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day10".to_owned(), factory::<Day10>);
+}
 
-    const TEST_DATA: &str = "89010123
+const TEST_DATA: &str = "89010123
 78121874
 87430965
 96549874
@@ -124,18 +104,33 @@ mod tests {
 01329801
 10456732";
 
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day10",
+    input: TEST_DATA,
+    part1: Some("36"),
+    part2: Some("81"),
+    factory: None,
+};
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs};
+
+    use super::*;
+
     #[test]
     fn test_basic_path_finding() {
-        let c = char_matrix(TEST_DATA);
+        let c = heights(TEST_DATA).unwrap();
 
-        let trails = climb_trail(&c, TextPoint { row: 0, col: 2 });
-        assert_eq!(trails.len(), 5);
+        let trails = trail_walker(&c).reachable(TextPoint { row: 0, col: 2 });
+        assert_eq!(trails.iter().filter(|p| c[**p] == Some(9)).count(), 5);
     }
 
     #[test]
     fn test_sample() {
-        let c = char_matrix(TEST_DATA);
-        let res = find_all_trails(&c);
+        let c = heights(TEST_DATA).unwrap();
+        let res = find_unique_trails(&c);
 
         assert_eq!(res.into_iter().map(|(_, n)| n).sum::<usize>(), 36);
     }
@@ -143,7 +138,7 @@ mod tests {
     #[test]
     fn test_sample_prob2_day10() {
         let data = TEST_DATA;
-        let c = char_matrix(&data);
+        let c = heights(data).unwrap();
 
         let res = find_all_trails(&c);
         assert_eq!(res.into_iter().map(|(_, n)| n).sum::<usize>(), 81);
@@ -152,7 +147,7 @@ mod tests {
     #[test]
     fn test_problem_day10() {
         let data = fs::read_to_string("data/day10.txt").unwrap();
-        let c = char_matrix(&data);
+        let c = heights(&data).unwrap();
 
         let res = find_unique_trails(&c);
         assert_eq!(res.into_iter().map(|(_, n)| n).sum::<usize>(), 796);
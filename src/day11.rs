@@ -1,6 +1,14 @@
 use std::collections::HashMap;
 
 use itertools::*;
+use linkme::distributed_slice;
+
+use crate::{
+    answer::Answer,
+    error::{parse_at, Result},
+    examples::{Example, EXAMPLES},
+    problem::{factory, variant_name, Problem, ProblemFactory, PROBLEMS},
+};
 
 fn maybe_split_even(i: usize) -> Option<Vec<usize>> {
     if i == 0 {
@@ -58,15 +66,104 @@ fn blink_with_counts(counts: &HashMap<usize, usize>) -> HashMap<usize, usize> {
     next
 }
 
-fn blink75(ns: Vec<usize>) -> HashMap<usize, usize> {
+fn blink_counted(ns: Vec<usize>, times: usize) -> HashMap<usize, usize> {
     let mut ns = ns.into_iter().counts();
-    println!("{ns:?}");
-    for _ in 0..75 {
+    for _ in 0..times {
         ns = blink_with_counts(&ns);
     }
     ns
 }
 
+fn blink75(ns: Vec<usize>) -> HashMap<usize, usize> {
+    blink_counted(ns, 75)
+}
+
+fn parse(data: &str) -> Result<Vec<usize>> {
+    data.split_whitespace().map(|s| parse_at(data, s)).collect()
+}
+
+pub struct Day11 {
+    stones: Vec<usize>,
+}
+
+impl Day11 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        Ok(Day11 {
+            stones: parse(data)?,
+        })
+    }
+
+    fn prob1_inner(&self) -> usize {
+        blink25(self.stones.clone()).len()
+    }
+
+    fn prob2_inner(&self) -> usize {
+        blink75(self.stones.clone()).values().sum()
+    }
+}
+
+impl Problem for Day11 {
+    fn parse(data: &str) -> Result<Self> {
+        Day11::with_data(data)
+    }
+
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
+    }
+
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner().into())
+    }
+}
+
+#[distributed_slice(PROBLEMS)]
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day11".to_owned(), factory::<Day11>);
+}
+
+// Counts the stones for both parts rather than keeping every one for part 1
+pub struct Day11Counted {
+    stones: Vec<usize>,
+}
+
+impl Problem for Day11Counted {
+    fn parse(data: &str) -> Result<Self> {
+        Ok(Day11Counted {
+            stones: parse(data)?,
+        })
+    }
+
+    fn prob1(&self) -> Result<Answer> {
+        Ok(blink_counted(self.stones.clone(), 25)
+            .values()
+            .sum::<usize>()
+            .into())
+    }
+
+    fn prob2(&self) -> Result<Answer> {
+        Ok(blink_counted(self.stones.clone(), 75)
+            .values()
+            .sum::<usize>()
+            .into())
+    }
+}
+
+#[distributed_slice(PROBLEMS)]
+fn register_day_counted(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert(variant_name("day11", "counted"), factory::<Day11Counted>);
+}
+
+const TEST_DATA_2: &str = "125 17";
+
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day11",
+    input: TEST_DATA_2,
+    part1: Some("55312"),
+    part2: None,
+    factory: None,
+};
+
 #[cfg(test)]
 mod tests {
     use std::{convert::identity, fs};
@@ -185,6 +282,12 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_example_day11_counted() {
+        let day11 = Day11Counted::parse(TEST_DATA_2).unwrap();
+        assert_eq!(day11.prob1(), Ok(Answer::Int(55312)));
+    }
+
     #[test]
     fn test_actual_problem_day11() {
         let data = fs::read_to_string("data/day11.txt").unwrap();
@@ -1,114 +1,84 @@
-use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+use linkme::distributed_slice;
 
 use crate::{
-    day4::{char_matrix, TextPoint},
-    day6::{explode_point_with_directions, Direction},
+    answer::Answer,
+    error::Result,
+    examples::{Example, EXAMPLES},
+    grid::Grid,
+    problem::{factory, Problem, ProblemFactory, PROBLEMS},
+    region::{regions, Region},
 };
 
-fn garden(cm: &Vec<Vec<u8>>) -> BTreeSet<BTreeSet<TextPoint>> {
-    let mut points: Vec<TextPoint> = cm
-        .iter()
-        .enumerate()
-        .flat_map(|(i, v)| {
-            v.iter()
-                .enumerate()
-                .map(move |(j, _)| TextPoint { row: i, col: j })
+pub struct Day12 {
+    regions: Vec<Region<u8>>,
+}
+
+impl Day12 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        Ok(Day12 {
+            regions: regions(&Grid::parse(data)?),
         })
-        .collect();
-
-    fn dfs(
-        cm: &Vec<Vec<u8>>,
-        stack: &mut Vec<TextPoint>,
-        visited: &mut BTreeSet<TextPoint>,
-        complete: &mut BTreeSet<BTreeSet<TextPoint>>,
-        current: &mut Option<(u8, BTreeSet<TextPoint>)>,
-    ) {
-        while let Some(next) = stack.pop() {
-            if visited.contains(&next) {
-                continue;
-            }
-            visited.insert(next);
-
-            let next_char = cm[next.row][next.col];
-
-            println!("{:?} {:?} {:?}", next, next_char, current);
-
-            // If we are already following a character, is the next one in it?
-            if let Some((c, p)) = current {
-                if *c == next_char {
-                    p.insert(next);
-                } else {
-                    complete.insert(p.clone());
-                    *current = Some((next_char, BTreeSet::from([next])));
-                }
-            } else {
-                *current = Some((next_char, BTreeSet::from([next])));
-            }
-
-            let cur_char = current.as_ref().unwrap().0;
-
-            // Push nodes with the same char onto the stack
-            explode_point_with_directions(
-                next,
-                TextPoint {
-                    row: cm.len() - 1,
-                    col: cm[0].len() - 1,
-                },
-                2,
-            )
-            .iter()
-            .filter_map(|(d, v)| {
-                if v.len() == 2
-                    && (*d == Direction::N
-                        || *d == Direction::E
-                        || *d == Direction::S
-                        || *d == Direction::W)
-                    && cm[v[1].row][v[1].col] == cur_char
-                {
-                    Some(v[1])
-                } else {
-                    None
-                }
-            })
-            .for_each(|p| {
-                if !visited.contains(&p) {
-                    stack.push(p);
-                }
-            });
-
-            dfs(cm, stack, visited, complete, current)
-        }
-        if let Some((_, p)) = current {
-            complete.insert(p.clone());
-        }
     }
 
-    let mut visited = BTreeSet::default();
-    let mut complete = BTreeSet::default();
-    let mut current = None;
-
-    dfs(cm, &mut points, &mut visited, &mut complete, &mut current);
+    fn prob1_inner(&self) -> usize {
+        self.regions.iter().map(|r| r.area() * r.perimeter).sum()
+    }
 
-    complete
+    // Bulk discount, where a whole straight side costs the same as one fence
+    fn prob2_inner(&self) -> usize {
+        self.regions.iter().map(|r| r.area() * r.sides).sum()
+    }
 }
 
-#[cfg(test)]
-mod tests {
+impl Problem for Day12 {
+    fn parse(data: &str) -> Result<Self> {
+        Day12::with_data(data)
+    }
 
-    use super::*;
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
+    }
+
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner().into())
+    }
+}
+
+#[distributed_slice(PROBLEMS)]
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day12".to_owned(), factory::<Day12>);
+}
 
-    const TEST_DATA: &str = "AAAA
+const TEST_DATA: &str = "AAAA
 BBCD
 BBCC
 EEEC";
 
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day12",
+    input: TEST_DATA,
+    part1: Some("140"),
+    part2: Some("80"),
+    factory: None,
+};
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::point::TextPoint;
+
     fn text_point(row: usize, col: usize) -> TextPoint {
         TextPoint { row, col }
     }
 
     #[test]
     fn test_build_a_garden() {
-        let c = char_matrix(TEST_DATA);
+        let c = Grid::parse(TEST_DATA).unwrap();
 
         let expected = BTreeSet::from([
             BTreeSet::from([
@@ -133,6 +103,17 @@ EEEC";
             BTreeSet::from([text_point(3, 0), text_point(3, 1), text_point(3, 2)]),
         ]);
 
-        assert_eq!(garden(&c), expected);
+        let garden: BTreeSet<BTreeSet<TextPoint>> = regions(&c)
+            .into_iter()
+            .map(|r| r.cells.into_iter().collect())
+            .collect();
+        assert_eq!(garden, expected);
+    }
+
+    #[test]
+    fn test_example_day12() {
+        let day12 = Day12::with_data(TEST_DATA).unwrap();
+        assert_eq!(day12.prob1_inner(), 140);
+        assert_eq!(day12.prob2_inner(), 80);
     }
 }
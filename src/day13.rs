@@ -1,5 +1,15 @@
+use std::collections::HashMap;
+
+use linkme::distributed_slice;
 use regex::Regex;
 
+use crate::{
+    answer::Answer,
+    error::{parse_at, Error, Result},
+    examples::{Example, EXAMPLES},
+    problem::{factory, Problem, ProblemFactory, PROBLEMS},
+};
+
 // I'll admit I totally stole this from here:
 // https://github.com/jixunmoe/aoc-2024/blob/main/aoc-2024/day-13/README.MD#part-1
 // I knew that you could come up with an equation to solve the matrix, I just hadn't
@@ -16,21 +26,17 @@ fn solve(x1: i64, x2: i64, xp: i64, y1: i64, y2: i64, yp: i64) -> Option<(i64, i
     }
 }
 
-fn parse(data: &str) -> Vec<[i64; 6]> {
+fn parse(data: &str) -> Result<Vec<[i64; 6]>> {
     let re = Regex::new(r"(?ms).*?(\d+).*?(\d+).*?(\d+).*?(\d+).*?(\d+).*?(\d+)").unwrap();
     let mut res = Vec::new();
     for section in data.split("\n\n") {
-        let caps = re.captures(section).unwrap();
-        res.push([
-            caps[1].parse().unwrap(),
-            caps[2].parse().unwrap(),
-            caps[3].parse().unwrap(),
-            caps[4].parse().unwrap(),
-            caps[5].parse().unwrap(),
-            caps[6].parse().unwrap(),
-        ]);
+        let caps = re
+            .captures(section)
+            .ok_or_else(|| Error::parse(data, section, "expected a machine with six numbers"))?;
+        let n = |i| parse_at(data, caps.get(i).unwrap().as_str());
+        res.push([n(1)?, n(2)?, n(3)?, n(4)?, n(5)?, n(6)?]);
     }
-    res
+    Ok(res)
 }
 
 fn cost(ns: [i64; 6]) -> Option<i64> {
@@ -38,14 +44,61 @@ fn cost(ns: [i64; 6]) -> Option<i64> {
     sol.map(|(a, b)| 3 * a + b)
 }
 
-#[cfg(test)]
-mod tests {
+// Part 2 moves every prize this far along both axes
+const PRIZE_OFFSET: i64 = 10000000000000;
 
-    use std::{convert::identity, fs};
+pub struct Day13 {
+    machines: Vec<[i64; 6]>,
+}
 
-    use super::*;
+impl Day13 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        Ok(Day13 {
+            machines: parse(data)?,
+        })
+    }
+
+    fn prob1_inner(&self) -> i64 {
+        self.machines.iter().filter_map(|ns| cost(*ns)).sum()
+    }
+
+    fn prob2_inner(&self) -> i64 {
+        self.machines
+            .iter()
+            .filter_map(|ns| {
+                cost([
+                    ns[0],
+                    ns[1],
+                    ns[2],
+                    ns[3],
+                    ns[4] + PRIZE_OFFSET,
+                    ns[5] + PRIZE_OFFSET,
+                ])
+            })
+            .sum()
+    }
+}
+
+impl Problem for Day13 {
+    fn parse(data: &str) -> Result<Self> {
+        Day13::with_data(data)
+    }
+
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
+    }
 
-    const TEST_DATA: &str = "Button A: X+94, Y+34
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner().into())
+    }
+}
+
+#[distributed_slice(PROBLEMS)]
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day13".to_owned(), factory::<Day13>);
+}
+
+const TEST_DATA: &str = "Button A: X+94, Y+34
 Button B: X+22, Y+67
 Prize: X=8400, Y=5400
 
@@ -62,6 +115,22 @@ Button B: X+27, Y+71
 Prize: X=18641, Y=10279
 ";
 
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day13",
+    input: TEST_DATA,
+    part1: Some("480"),
+    part2: None,
+    factory: None,
+};
+
+#[cfg(test)]
+mod tests {
+
+    use std::{convert::identity, fs};
+
+    use super::*;
+
     #[test]
     fn test_day13_solve_examples() {
         assert_eq!(solve(94, 22, 8400, 34, 67, 5400), Some((80, 40)));
@@ -73,7 +142,7 @@ Prize: X=18641, Y=10279
     #[test]
     fn test_day13_parse() {
         assert_eq!(
-            parse(TEST_DATA),
+            parse(TEST_DATA).unwrap(),
             vec![
                 [94, 34, 22, 67, 8400, 5400],
                 [26, 66, 67, 21, 12748, 12176],
@@ -85,7 +154,7 @@ Prize: X=18641, Y=10279
 
     #[test]
     fn test_day13_cost_example() {
-        let data = parse(TEST_DATA);
+        let data = parse(TEST_DATA).unwrap();
 
         let res: Vec<_> = data.into_iter().map(cost).collect();
 
@@ -95,7 +164,7 @@ Prize: X=18641, Y=10279
     #[test]
     fn test_day13_actual() {
         let data = fs::read_to_string("data/day13.txt").unwrap();
-        let data = parse(&data);
+        let data = parse(&data).unwrap();
 
         let res: i64 = data.iter().map(|ns| cost(*ns)).filter_map(identity).sum();
 
@@ -1,92 +1,183 @@
-use itertools::*;
+use std::collections::HashMap;
+
+use linkme::distributed_slice;
 use regex::Regex;
 
-// Technically this is a u64 but I'm so sick of the hoops with unsigned math
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-struct Point(i64, i64);
+use crate::{
+    answer::Answer,
+    coord::{Coord, Vector},
+    error::{parse_at, Error, Result},
+    examples::{Example, EXAMPLES},
+    grid::{Grid, SparseGrid},
+    point::TextPoint,
+    problem::{factory, Problem, ProblemFactory, PROBLEMS},
+    render::Renderer,
+    trace::{self, trace, Level},
+};
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-struct Velocity(i64, i64);
+struct Robot(Coord, Vector);
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-struct Robot(Point, Velocity);
+// The robots on a floor that wraps round, so walking off one edge brings them
+// back on at the other. Each position has the velocities of the robots on it.
+type Floor = SparseGrid<Vec<Vector>>;
+
+fn place(width: i64, height: i64, robots: &[Robot]) -> Floor {
+    let mut floor = Floor::toroidal(width, height);
+    for Robot(p, v) in robots {
+        floor.entry(*p).or_default().push(*v);
+    }
+    floor
+}
 
-fn wrap(value: i64, limit: i64) -> i64 {
-    if value < 0 {
-        limit + value
-    } else {
-        value % limit
+// Every robot takes a step at once
+fn tick(floor: &Floor) -> Floor {
+    let size = floor.size().expect("the floor wraps round");
+    let mut next = Floor::toroidal(size.col, size.row);
+    for (p, vs) in floor.iter() {
+        for v in vs {
+            next.entry(p + *v).or_default().push(*v);
+        }
     }
+    next
 }
 
-fn score(rs: Vec<Robot>, corner: Point) -> i64 {
-    // This only works if our corner is even, but in this problem it always is
-    let center = Point(corner.0 / 2, corner.1 / 2);
+fn score(floor: &Floor) -> i64 {
+    // This only works if the floor is an odd size, but in this problem it always is
+    let size = floor.size().expect("the floor wraps round");
+    let center = Coord::new(size.row / 2, size.col / 2);
 
     let mut q_counts = [0, 0, 0, 0];
 
-    for r in rs {
-        if r.0 .0 < center.0 && r.0 .1 < center.1 {
-            q_counts[0] += 1;
-        } else if r.0 .0 < center.0 && r.0 .1 > center.1 {
-            q_counts[1] += 1;
-        } else if r.0 .0 > center.0 && r.0 .1 < center.1 {
-            q_counts[2] += 1;
-        } else if r.0 .0 > center.0 && r.0 .1 > center.1 {
-            q_counts[3] += 1;
+    for (p, vs) in floor.iter() {
+        let n = vs.len() as i64;
+        if p.row < center.row && p.col < center.col {
+            q_counts[0] += n;
+        } else if p.row < center.row && p.col > center.col {
+            q_counts[1] += n;
+        } else if p.row > center.row && p.col < center.col {
+            q_counts[2] += n;
+        } else if p.row > center.row && p.col > center.col {
+            q_counts[3] += n;
         }
     }
 
     q_counts.iter().product()
 }
 
-impl Robot {
-    fn tick(&self, corner: Point) -> Robot {
-        let next = Point(
-            wrap(self.0 .0 + self.1 .0, corner.0 + 1),
-            wrap(self.0 .1 + self.1 .1, corner.1 + 1),
-        );
-        Robot(next, self.1)
-    }
-}
-
-fn parse(data: &str) -> Vec<Robot> {
+fn parse(data: &str) -> Result<Vec<Robot>> {
     let re = Regex::new(r"p=(\d+),(\d+) v=(-?\d+),(-?\d+)").unwrap();
 
     data.lines()
         .map(|line| {
-            let caps = re.captures(line).unwrap();
-            Robot(
-                Point(caps[1].parse().unwrap(), caps[2].parse().unwrap()),
-                Velocity(caps[3].parse().unwrap(), caps[4].parse().unwrap()),
-            )
+            let caps = re
+                .captures(line)
+                .ok_or_else(|| Error::parse(data, line, "expected `p=x,y v=dx,dy`"))?;
+            let n = |i| parse_at(data, caps.get(i).unwrap().as_str());
+            // The puzzle gives x,y, which is col,row
+            Ok(Robot(Coord::new(n(2)?, n(1)?), Vector::new(n(4)?, n(3)?)))
         })
         .collect()
 }
 
-fn print_robots(rs: &Vec<Robot>, corner: Point) {
-    let cs = rs.into_iter().map(|r| r.0).counts();
+// How many robots are on each tile, if anyone's looking
+fn trace_robots(floor: &Floor) {
+    if !trace::enabled(Level::Debug) {
+        return;
+    }
+
+    let size = floor.size().expect("the floor wraps round");
+    let tiles = Grid::filled(size.col as usize, size.row as usize, b'.');
+    let counts = floor
+        .iter()
+        .filter_map(|(p, vs)| Some((TextPoint::try_from(p).ok()?, vs.len())));
+
+    Renderer::new(&tiles).heatmap(counts).trace(Level::Debug);
+}
+
+// More robots than this on one row, and on one column, is the tree's border.
+// The border puts 35 on each, while before it the robots never manage more
+// than 15 on a row and a column at once.
+const BORDER: usize = 25;
+
+// The tree picture has a border, which lines lots of robots up on a single row
+// and a single column at the same time. Either one alone happens by chance.
+fn heuristic(floor: &Floor) -> bool {
+    let mut rows: HashMap<i64, usize> = HashMap::new();
+    let mut cols: HashMap<i64, usize> = HashMap::new();
+    for (p, vs) in floor.iter() {
+        *rows.entry(p.row).or_default() += vs.len();
+        *cols.entry(p.col).or_default() += vs.len();
+    }
+
+    rows.values().any(|v| *v > BORDER) && cols.values().any(|v| *v > BORDER)
+}
+
+// The real puzzle is always 101 wide and 103 tall
+const WIDTH: i64 = 101;
+const HEIGHT: i64 = 103;
+
+pub struct Day14 {
+    // Where the robots start
+    floor: Floor,
+}
+
+impl Day14 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        Day14::with_size(data, WIDTH, HEIGHT)
+    }
+
+    // The examples are on a smaller floor
+    pub fn with_size(data: &str, width: i64, height: i64) -> Result<Self> {
+        Ok(Day14 {
+            floor: place(width, height, &parse(data)?),
+        })
+    }
 
-    for x in 0..=corner.0 {
-        for y in 0..=corner.1 {
-            if let Some(c) = cs.get(&Point(x, y)) {
-                print!("{}", c);
-            } else {
-                print!(".")
+    fn prob1_inner(&self) -> i64 {
+        let mut floor = self.floor.clone();
+        for _ in 0..100 {
+            floor = tick(&floor);
+        }
+        score(&floor)
+    }
+
+    fn prob2_inner(&self) -> Result<i64> {
+        let size = self.floor.size().expect("the floor wraps round");
+        let mut floor = self.floor.clone();
+        // The robots are all back where they started after width * height seconds
+        for i in 1..=(size.row * size.col) {
+            floor = tick(&floor);
+            if heuristic(&floor) {
+                trace!(Level::Info, "the robots line up after {i} seconds");
+                trace_robots(&floor);
+                return Ok(i);
             }
         }
-        print!("\n");
+        Err(Error::solve("the robots never line up into a picture"))
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use core::time;
-    use std::{fs, thread};
+impl Problem for Day14 {
+    fn parse(data: &str) -> Result<Self> {
+        Day14::with_data(data)
+    }
 
-    use super::*;
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
+    }
 
-    const TEST_DATA: &str = "p=0,4 v=3,-3
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner()?.into())
+    }
+}
+
+#[distributed_slice(PROBLEMS)]
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day14".to_owned(), factory::<Day14>);
+}
+
+const TEST_DATA: &str = "p=0,4 v=3,-3
 p=6,3 v=-1,-3
 p=10,3 v=-1,2
 p=2,0 v=2,-1
@@ -100,93 +191,95 @@ p=2,4 v=2,-3
 p=9,5 v=-3,-3
 ";
 
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day14",
+    input: TEST_DATA,
+    part1: Some("12"),
+    part2: None,
+    factory: Some(|data| Ok(Box::new(Day14::with_size(data, 11, 7)?))),
+};
+
+#[cfg(test)]
+mod tests {
+    use core::time;
+    use std::{fs, thread};
+
+    use super::*;
+
     #[test]
     fn test_parse() {
         let test = "p=0,4 v=3,-3";
-        assert_eq!(parse(test), vec![Robot(Point(0, 4), Velocity(3, -3))]);
+        assert_eq!(
+            parse(test).unwrap(),
+            vec![Robot(Coord::new(4, 0), Vector::new(-3, 3))]
+        );
     }
 
     #[test]
     fn test_wrap() {
-        let corner = Point(10, 6);
-        let v = Velocity(2, -3);
-        let r = Robot(Point(2, 4), v);
-
-        let r = r.tick(corner);
-        assert_eq!(r, Robot(Point(4, 1), v));
-
-        let r = r.tick(corner);
-        assert_eq!(r, Robot(Point(6, 5), v));
-
-        let r = r.tick(corner);
-        assert_eq!(r, Robot(Point(8, 2), v));
-
-        let r = r.tick(corner);
-        assert_eq!(r, Robot(Point(10, 6), v));
-
-        let r = r.tick(corner);
-        assert_eq!(r, Robot(Point(1, 3), v));
+        let v = Vector::new(-3, 2);
+        let mut floor = place(11, 7, &[Robot(Coord::new(4, 2), v)]);
+
+        for p in [(1, 4), (5, 6), (2, 8), (6, 10), (3, 1)] {
+            floor = tick(&floor);
+            assert_eq!(
+                floor.iter().collect::<Vec<_>>(),
+                vec![(Coord::new(p.0, p.1), &vec![v])]
+            );
+        }
     }
 
     #[test]
     fn test_run_14() {
-        let mut rs = parse(TEST_DATA);
-
-        let corner = Point(10, 6);
-
-        for _ in 0..100 {
-            rs = rs.into_iter().map(|r| r.tick(corner)).collect();
-        }
-
-        assert_eq!(score(rs, corner), 12);
+        let day14 = Day14::with_size(TEST_DATA, 11, 7).unwrap();
+        assert_eq!(day14.prob1_inner(), 12);
     }
 
     #[test]
     fn test_actual_14() {
         let data = fs::read_to_string("data/day14.txt").unwrap();
-        let mut rs = parse(&data);
-
-        let corner = Point(100, 102);
+        let day14 = Day14::with_data(&data).unwrap();
+        assert_eq!(day14.prob1_inner(), 229632480);
+    }
 
-        for _ in 0..100 {
-            rs = rs.into_iter().map(|r| r.tick(corner)).collect();
-        }
+    #[test]
+    fn test_actual_14_part2() {
+        let data = fs::read_to_string("data/day14.txt").unwrap();
+        let day14 = Day14::with_data(&data).unwrap();
 
-        assert_eq!(score(rs, corner), 229632480);
+        assert_eq!(day14.prob2_inner(), Ok(7051));
     }
 
-    fn heuristic(rs: &Vec<Robot>) -> bool {
-        let ys = rs.iter().map(|r| r.0 .1).counts();
-
-        for v in ys.values() {
-            if *v > 25 {
-                return true;
-            }
-        }
-        return false;
+    #[test]
+    fn test_run_14_no_picture() {
+        // Too few robots for a border, so every frame fails the heuristic
+        let day14 = Day14::with_size(TEST_DATA, 11, 7).unwrap();
+        assert_eq!(
+            day14.prob2_inner(),
+            Err(Error::solve("the robots never line up into a picture"))
+        );
     }
 
     //#[test]
     //fn test_run_14_part2() {
     //    let data = fs::read_to_string("data/day14.txt").unwrap();
-    //    let mut rs = parse(&data);
-    //
-    //    let corner = Point(100, 102);
+    //    let mut floor = place(WIDTH, HEIGHT, &parse(&data).unwrap());
     //
     //    let one_second = time::Duration::from_millis(1000);
     //
     //    for i in 0..10000 {
-    //        rs = rs.into_iter().map(|r| r.tick(corner)).collect();
+    //        floor = tick(&floor);
     //
-    //        if heuristic(&rs) {
+    //        if heuristic(&floor) {
     //            println!("{}", i + 1);
-    //            print_robots(&rs, corner);
+    //            trace_robots(&floor);
     //            thread::sleep(one_second);
     //        }
     //    }
     //
     //    // The answer is 7051 and it's really obvious which one it is
     //
-    //    //assert_eq!(score(rs, corner), 12);
+    //    //assert_eq!(score(&floor), 12);
     //}
 }
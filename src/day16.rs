@@ -1,231 +1,134 @@
-use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap, HashSet};
-
-use crate::day4::char_matrix;
-use crate::day4::TextPoint;
-use crate::day6::{explode_point_with_directions, Direction};
-
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-struct State {
-    cost: usize,
-    position: TextPoint,
-    direction: Direction,
-}
-// The priority queue depends on `Ord`.
-// // Explicitly implement the trait so the queue becomes a min-heap
-// // instead of a max-heap.
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        // Notice that we flip the ordering on costs.
-        // In case of a tie we compare positions - this step is necessary
-        // to make implementations of `PartialEq` and `Ord` consistent.
-        other
-            .cost
-            .cmp(&self.cost)
-            .then_with(|| self.position.cmp(&other.position))
-    }
-}
-
-// `PartialOrd` needs to be implemented as well.
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-fn find_edges(
-    graph: &Vec<Vec<u8>>,
-    position: TextPoint,
-    direction: Direction,
-) -> Vec<(usize, TextPoint, Direction)> {
-    explode_point_with_directions(
-        position,
-        TextPoint {
-            row: graph.len() - 1,
-            col: graph[0].len() - 1,
-        },
-        2,
-    )
-    .iter()
-    .filter_map(|(d, v)| {
-        if v.len() != 2 || graph[v[1].row][v[1].col] == b'#' {
-            None
-        } else if *d == direction && graph[v[1].row][v[1].col] != b'#' {
-            Some((1, v[1], *d))
-        } else if *d == direction.rotate_right() {
-            Some((1001, v[1], *d))
-        } else if *d == direction.rotate_left() {
-            Some((1001, v[1], *d))
-        } else {
-            None
-        }
-    })
-    .collect()
-}
-
-// Start at `start` and use `dist` to track the current shortest distance
-// // to each node. This implementation isn't memory-efficient as it may leave duplicate
-// // nodes in the queue. It also uses `usize::MAX` as a sentinel value,
-// // for a simpler implementation.
+use std::collections::{HashMap, HashSet};
+
+use linkme::distributed_slice;
+
+use crate::answer::Answer;
+use crate::cell::Terrain;
+use crate::direction::Cardinal;
+use crate::error::{Error, Result};
+use crate::examples::{Example, EXAMPLES};
+use crate::grid::Grid;
+use crate::movement::{Movement, Vehicle};
+use crate::point::TextPoint;
+use crate::problem::{factory, Problem, ProblemFactory, PROBLEMS};
+use crate::render::{Renderer, Rgb};
+use crate::search::dijkstra;
+use crate::trace::{self, trace, Level};
+
+// Stepping forward costs 1, and every quarter turn costs 1000. Turning comes
+// with a step, since there's never any point turning to face a wall.
+const REINDEER: Movement = Movement {
+    forward: 1,
+    turn_left: 1000,
+    turn_right: 1000,
+    reverse: Some(2000),
+    turn_in_place: false,
+    start_heading: Cardinal::E,
+    end_heading: None,
+};
+
+// The cheapest score from `start` to `goal`, and how many seats are on any of
+// the cheapest ways there
 fn shortest_path(
-    graph: &Vec<Vec<u8>>,
+    graph: &Grid<Terrain>,
     start: TextPoint,
     goal: TextPoint,
 ) -> Option<(usize, usize)> {
-    // dist[node] = current shortest distance from `start` to `node`
-    let mut dist: HashMap<_, _> = graph
-        .iter()
-        .enumerate()
-        .flat_map(|(r, row)| {
-            row.iter()
-                .enumerate()
-                .map(move |(c, _)| (TextPoint { row: r, col: c }, usize::MAX))
-        })
-        .collect();
+    let reindeer = Vehicle {
+        maze: graph,
+        movement: REINDEER,
+    };
+    let search = dijkstra(&reindeer, (start, REINDEER.start_heading), |s| {
+        REINDEER.arrived(s, goal)
+    });
+    let res = search.cost()?;
+
+    // A seat counts whichever way the reindeer was facing on it
+    let seats: HashSet<TextPoint> = search.on_best_paths().into_iter().map(|(p, _)| p).collect();
+
+    trace!(
+        Level::Info,
+        "{res} to get round, passing {} seats",
+        seats.len()
+    );
+    if trace::enabled(Level::Debug) {
+        // The heatmap has the cheapest way onto each cell, facing any way
+        let mut dist: HashMap<TextPoint, usize> = HashMap::new();
+        for ((p, _), d) in search.distances() {
+            let e = dist.entry(*p).or_insert(d);
+            *e = (*e).min(d);
+        }
 
-    let mut prev: HashMap<TextPoint, Vec<TextPoint>> = HashMap::new();
+        Renderer::new(&graph.to_chars())
+            .heatmap(dist)
+            .path(b'O', Rgb::GREEN, "best seats", seats.iter().copied())
+            .marker(start, b'S', Rgb::YELLOW, "start")
+            .marker(goal, b'E', Rgb::RED, "end")
+            .trace(Level::Debug);
+    }
 
-    let mut heap = BinaryHeap::new();
+    Some((res, seats.len()))
+}
 
-    // We're at `start`, with a zero cost
-    *dist.get_mut(&start).unwrap() = 0;
-    heap.push(State {
-        cost: 0,
-        position: start,
-        direction: Direction::E,
-    });
+// The maze, and where the start and end are
+fn parse(data: &str) -> Result<(Grid<Terrain>, TextPoint, TextPoint)> {
+    let (maze, markers) = Grid::parse_cells(data, &[(b'S', Terrain::Open), (b'E', Terrain::Open)])?;
+    let find = |c: u8| {
+        markers
+            .one(c)
+            .ok_or_else(|| Error::parse(data, data, format!("the maze needs one `{}`", c as char)))
+    };
+    Ok((maze, find(b'S')?, find(b'E')?))
+}
 
-    // Examine the frontier with lower cost nodes first (min-heap)
-    let mut res = None;
-    while let Some(State {
-        cost,
-        position,
-        direction,
-    }) = heap.pop()
-    {
-        if position == goal {
-            res = Some(cost);
-        }
+pub struct Day16 {
+    // The best score and how many seats are on a best path, or None if
+    // there's no way through. Both parts come from the one search.
+    best: Option<(usize, usize)>,
+}
 
-        // Don't bother with non-shortest routes
-        if let Some(res) = res {
-            if cost > res {
-                continue;
-            }
-        }
+impl Day16 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        let (maze, start, end) = parse(data)?;
+        Ok(Day16 {
+            best: shortest_path(&maze, start, end),
+        })
+    }
 
-        // Important as we may have already found a better way
-        if cost > dist[&position] {
-            continue;
-        }
+    fn best(&self) -> Result<(usize, usize)> {
+        self.best
+            .ok_or_else(|| Error::solve("there is no way from S to E"))
+    }
 
-        // For each node we can reach, see if we can find a way with
-        // a lower cost going through this node
-        for (next_cost, next_position, next_direction) in find_edges(graph, position, direction) {
-            let next = State {
-                cost: cost + next_cost,
-                position: next_position,
-                direction: next_direction,
-            };
-
-            //println!(
-            //    "{cost:?} {next_cost:?} {:?}",
-            //    prev.get(&TextPoint { row: 9, col: 3 })
-            //);
-
-            if let Some(parents) = prev.get_mut(&next_position) {
-                parents.push(position);
-            } else {
-                prev.insert(next_position, vec![position]);
-            }
-
-            // If so, add it to the frontier and continue
-            if next.cost <= dist[&next.position] {
-                heap.push(next);
-                // Relaxation, we have now found a better way
-                *dist.get_mut(&next_position).unwrap() = cost + next_cost;
-            }
-        }
-        //for k in 0..graph.len() {
-        //    for j in 0..graph[0].len() {
-        //        let v = dist[&TextPoint { row: k, col: j }];
-        //        if v == usize::MAX {
-        //            print!("..... ");
-        //        } else {
-        //            print!("{:>5} ", v);
-        //        }
-        //    }
-        //    println!("");
-        //}
+    fn prob1_inner(&self) -> Result<usize> {
+        Ok(self.best()?.0)
     }
-    for k in 0..graph.len() {
-        for j in 0..graph[0].len() {
-            let v = dist[&TextPoint { row: k, col: j }];
-            if v == usize::MAX {
-                print!("..... ");
-            } else {
-                print!("{:>5} ", v);
-            }
-        }
-        println!("");
+
+    fn prob2_inner(&self) -> Result<usize> {
+        Ok(self.best()?.1)
     }
+}
 
-    // Traverse the graph
-    fn dfs(
-        prev: &HashMap<TextPoint, Vec<TextPoint>>,
-        visited: &mut HashSet<TextPoint>,
-        current: &TextPoint,
-        dist: &HashMap<TextPoint, usize>,
-    ) {
-        if visited.contains(current) {
-            return;
-        }
-        visited.insert(*current);
-        //for k in 0..17 {
-        //    for j in 0..17 {
-        //        let v = &TextPoint { row: k, col: j };
-        //        if visited.contains(v) {
-        //            print!("O");
-        //        } else {
-        //            print!(".");
-        //        }
-        //    }
-        //    println!("");
-        //}
-
-        if let Some(v) = prev.get(current) {
-            v.iter()
-                //.inspect(|c| println!("{:?} {:?} {:?}: {:?}", current, dist[&current], c, dist[&c]))
-                .for_each(|c| dfs(prev, visited, c, dist));
-        }
+impl Problem for Day16 {
+    fn parse(data: &str) -> Result<Self> {
+        Day16::with_data(data)
     }
 
-    if let Some(res) = res {
-        let mut visited = HashSet::new();
-        //visited.insert(TextPoint { row: 1, col: 14 });
-        dfs(&prev, &mut visited, &goal, &dist);
-        Some((res, visited.len()))
-    } else {
-        None
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner()?.into())
     }
 
-    //if let Some(res) = res {
-    //    Some((res, prev.len()))
-    //} else {
-    //    None
-    //}
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner()?.into())
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use core::time;
-    use std::{fs, thread};
-
-    use crate::day4::find_char_in_puzzle;
-
-    use super::*;
+#[distributed_slice(PROBLEMS)]
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day16".to_owned(), factory::<Day16>);
+}
 
-    const TEST_DATA: &str = "###############
+const TEST_DATA: &str = "###############
 #.......#....E#
 #.#.###.#.###.#
 #.....#.#...#.#
@@ -242,60 +145,82 @@ mod tests {
 ###############
 ";
 
+const TEST_DATA_2: &str = "#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################";
+
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day16",
+    input: TEST_DATA,
+    part1: Some("7036"),
+    part2: Some("45"),
+    factory: None,
+};
+
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE_2: Example = Example {
+    day: "day16",
+    input: TEST_DATA_2,
+    part1: Some("11048"),
+    part2: Some("64"),
+    factory: None,
+};
+
+#[cfg(test)]
+mod tests {
+    use core::time;
+    use std::{fs, thread};
+
+    use super::*;
+
     #[test]
     fn test_find_edges() {
-        let g = char_matrix(TEST_DATA);
+        let (g, _, _) = parse(TEST_DATA).unwrap();
 
-        let res = find_edges(&g, TextPoint { row: 13, col: 1 }, Direction::E);
+        let res = REINDEER.moves(&g, TextPoint { row: 13, col: 1 }, Cardinal::E);
 
         assert_eq!(res.len(), 2);
 
         let (cost, position, direction) = res[1];
         assert_eq!(
             (cost, position, direction),
-            (1, TextPoint { row: 13, col: 2 }, Direction::E)
+            (1, TextPoint { row: 13, col: 2 }, Cardinal::E)
         );
         let (cost, position, direction) = res[0];
         assert_eq!(
             (cost, position, direction),
-            (7001, TextPoint { row: 12, col: 1 }, Direction::N)
+            (1001, TextPoint { row: 12, col: 1 }, Cardinal::N)
         );
     }
 
     #[test]
     fn test_example_day16() {
-        let g = char_matrix(TEST_DATA);
-        let start = find_char_in_puzzle(&g, b'S')[0];
-        let end = find_char_in_puzzle(&g, b'E')[0];
+        let (g, start, end) = parse(TEST_DATA).unwrap();
 
         let res = shortest_path(&g, start, end);
 
         assert_eq!(res, Some((7036, 45)));
     }
 
-    const TEST_DATA_2: &str = "#################
-#...#...#...#..E#
-#.#.#.#.#.#.#.#.#
-#.#.#.#...#...#.#
-#.#.#.#.###.#.#.#
-#...#.#.#.....#.#
-#.#.#.#.#.#####.#
-#.#...#.#.#.....#
-#.#.#####.#.###.#
-#.#.#.......#...#
-#.#.###.#####.###
-#.#.#...#.....#.#
-#.#.#.#####.###.#
-#.#.#.........#.#
-#.#.#.#########.#
-#S#.............#
-#################";
     #[test]
 
     fn test_example_2_day16() {
-        let g = char_matrix(TEST_DATA_2);
-        let start = find_char_in_puzzle(&g, b'S')[0];
-        let end = find_char_in_puzzle(&g, b'E')[0];
+        let (g, start, end) = parse(TEST_DATA_2).unwrap();
 
         let res = shortest_path(&g, start, end);
 
@@ -305,12 +230,10 @@ mod tests {
     #[test]
     fn test_actual_16() {
         let data = fs::read_to_string("data/day16.txt").unwrap();
-        let g = char_matrix(&data);
-        let start = find_char_in_puzzle(&g, b'S')[0];
-        let end = find_char_in_puzzle(&g, b'E')[0];
+        let (g, start, end) = parse(&data).unwrap();
 
         let res = shortest_path(&g, start, end);
 
-        assert_eq!(res, Some((102460, 4)));
+        assert_eq!(res, Some((102460, 527)));
     }
 }
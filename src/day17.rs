@@ -1,3 +1,15 @@
+use std::collections::HashMap;
+
+use linkme::distributed_slice;
+use regex::Regex;
+
+use crate::{
+    answer::Answer,
+    error::{parse_at, Error, Result},
+    examples::{Example, EXAMPLES},
+    problem::{factory, Problem, ProblemFactory, PROBLEMS},
+};
+
 #[derive(Clone, Debug)]
 struct Computer {
     program: Vec<u8>,
@@ -65,18 +77,153 @@ impl Computer {
             _ => panic!("Invalid combo operand"),
         }
     }
+
+    fn run(mut self) -> Vec<i64> {
+        while !self.is_done() {
+            self = self.step();
+        }
+        self.output
+    }
+}
+
+fn parse(data: &str) -> Result<Computer> {
+    let re = Regex::new(
+        r"Register A: (\d+)\s+Register B: (\d+)\s+Register C: (\d+)\s+Program: ([\d,]+)",
+    )
+    .unwrap();
+    let caps = re
+        .captures(data)
+        .ok_or_else(|| Error::parse(data, data, "expected three registers and a program"))?;
+    let field = |i| caps.get(i).unwrap().as_str();
+
+    Ok(Computer::new(
+        field(4)
+            .split(",")
+            .map(|s| parse_at(data, s))
+            .collect::<Result<_>>()?,
+        parse_at(data, field(1))?,
+        parse_at(data, field(2))?,
+        parse_at(data, field(3))?,
+    ))
+}
+
+// The program only ever looks at the bottom three bits of A before shifting them
+// away, so we can build A up three bits at a time from the last output backwards
+fn find_quine_a(program: &[u8], ar: i64, target_idx: usize) -> Option<i64> {
+    if target_idx == usize::MAX {
+        return Some(ar);
+    }
+    for i in 0..8 {
+        let next_a = ar * 8 + i;
+        let output = Computer::new(program.to_vec(), next_a, 0, 0).run();
+        if output[0] == program[target_idx] as i64 {
+            let best_a = find_quine_a(program, next_a, target_idx.wrapping_sub(1));
+            if best_a.is_some() {
+                return best_a;
+            }
+        }
+    }
+
+    None
+}
+
+pub struct Day17 {
+    computer: Computer,
+}
+
+impl Day17 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        Ok(Day17 {
+            computer: parse(data)?,
+        })
+    }
+
+    fn prob1_inner(&self) -> Vec<i64> {
+        self.computer.clone().run()
+    }
+
+    fn prob2_inner(&self) -> Result<i64> {
+        let program = &self.computer.program;
+        find_quine_a(program, 0, program.len() - 1)
+            .ok_or_else(|| Error::solve("no value of A makes the program print itself"))
+    }
 }
 
+impl Problem for Day17 {
+    fn parse(data: &str) -> Result<Self> {
+        Day17::with_data(data)
+    }
+
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
+    }
+
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner()?.into())
+    }
+}
+
+#[distributed_slice(PROBLEMS)]
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day17".to_owned(), factory::<Day17>);
+}
+
+const TEST_DATA: &str = "Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0
+";
+
+const TEST_DATA_2: &str = "Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0
+";
+
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day17",
+    input: TEST_DATA,
+    part1: Some("4,6,3,5,6,3,5,2,1,0"),
+    part2: None,
+    factory: None,
+};
+
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE_2: Example = Example {
+    day: "day17",
+    input: TEST_DATA_2,
+    part1: None,
+    part2: Some("117440"),
+    factory: None,
+};
+
 #[cfg(test)]
 mod tests {
     use core::time;
     use std::{fs, num, thread};
 
-    use crate::day4::find_char_in_puzzle;
-
     use super::*;
 
-    const TEST_DATA: &str = "";
+    #[test]
+    fn test_parse_17() {
+        let c = parse(TEST_DATA).unwrap();
+
+        assert_eq!(c.ar, 729);
+        assert_eq!(c.program, vec![0, 1, 5, 4, 3, 0]);
+        assert_eq!(c.run(), vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_actual_17() {
+        let data = fs::read_to_string("data/day17.txt").unwrap();
+        let day17 = Day17::with_data(&data).unwrap();
+
+        assert_eq!(day17.prob1_inner(), vec![1, 5, 0, 3, 7, 3, 0, 3, 1]);
+        assert_eq!(day17.prob2_inner(), Ok(105981155568026));
+    }
 
     #[test]
     fn test_example1() {
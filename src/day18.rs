@@ -1,182 +1,146 @@
-use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::HashMap;
+
+use linkme::distributed_slice;
+
+use crate::answer::Answer;
+use crate::blocking::{first_blocking, Blockage, OnFoot};
+use crate::cell::Terrain;
+use crate::error::{parse_at, Error, Result};
+use crate::examples::{Example, EXAMPLES};
+use crate::grid::Grid;
+use crate::point::TextPoint;
+use crate::problem::{factory, Problem, ProblemFactory, PROBLEMS};
+use crate::render::Renderer;
+use crate::search::astar;
+use crate::trace::{self, Level};
+
+fn shortest_path(graph: &Grid<Terrain>) -> Option<usize> {
+    let start = TextPoint { row: 0, col: 0 };
+    let goal = graph.corner();
 
-use crate::day4::char_matrix;
-use crate::day4::TextPoint;
-use crate::day6::{explode_point_with_directions, Direction};
+    let memory = OnFoot { grid: graph, goal };
+    // A byte on the start cuts it off, the same as one on the exit
+    if !memory.open(start) {
+        return None;
+    }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-struct State {
-    cost: usize,
-    position: TextPoint,
+    let search = astar(&memory, start, |p| *p == goal);
+    if trace::enabled(Level::Debug) {
+        Renderer::new(&graph.to_chars())
+            .heatmap(search.distances().map(|(p, d)| (*p, d)))
+            .trace(Level::Debug);
+    }
+    search.cost()
 }
 
-// The priority queue depends on `Ord`.
-// // Explicitly implement the trait so the queue becomes a min-heap
-// // instead of a max-heap.
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        // Notice that we flip the ordering on costs.
-        // In case of a tie we compare positions - this step is necessary
-        // to make implementations of `PartialEq` and `Ord` consistent.
-        other
-            .cost
-            .cmp(&self.cost)
-            .then_with(|| self.position.cmp(&other.position))
-    }
+// Every byte has to land inside a memory space `size` across
+fn parse(data: &str, size: usize) -> Result<Vec<(usize, usize)>> {
+    data.lines()
+        .map(|line| {
+            // Yes this is weird but the problem uses x,y
+            let Some((col, row)) = line.split_once(",") else {
+                return Err(Error::parse(data, line, "expected `x,y`"));
+            };
+            let (row, col) = (parse_at(data, row)?, parse_at(data, col)?);
+            if row >= size || col >= size {
+                return Err(Error::parse(
+                    data,
+                    line,
+                    format!("expected both less than {size}"),
+                ));
+            }
+            Ok((row, col))
+        })
+        .collect()
 }
 
-// `PartialOrd` needs to be implemented as well.
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+fn build_graph(rows: usize, cols: usize, blobs: &[(usize, usize)]) -> Grid<Terrain> {
+    let mut res = Grid::filled(cols, rows, Terrain::Open);
+
+    for &(row, col) in blobs {
+        res[TextPoint { row, col }] = Terrain::Wall;
     }
-}
 
-fn find_edges(graph: &Vec<Vec<u8>>, position: TextPoint) -> Vec<(usize, TextPoint)> {
-    explode_point_with_directions(
-        position,
-        TextPoint {
-            row: graph.len() - 1,
-            col: graph[0].len() - 1,
-        },
-        2,
-    )
-    .iter()
-    .filter_map(|(d, v)| {
-        if v.len() != 2 || graph[v[1].row][v[1].col] == b'#' {
-            None
-        } else if *d == Direction::N
-            || *d == Direction::E
-            || *d == Direction::S
-            || *d == Direction::W
-        {
-            Some((1, v[1]))
-        } else {
-            None
-        }
-    })
-    .collect()
+    res
 }
 
-// Start at `start` and use `dist` to track the current shortest distance
-// to each node. This implementation isn't memory-efficient as it may leave duplicate
-// nodes in the queue. It also uses `usize::MAX` as a sentinel value,
-// for a simpler implementation.
-fn shortest_path(graph: &Vec<Vec<u8>>) -> Option<usize> {
-    // dist[node] = current shortest distance from `start` to `node`
-    let mut dist: HashMap<_, _> = graph
+// When the bytes first cut the top left off from the bottom right
+fn cut_off(size: usize, bytes: &[(usize, usize)]) -> Option<Blockage> {
+    let obstacles: Vec<_> = bytes
         .iter()
-        .enumerate()
-        .flat_map(|(r, row)| {
-            row.iter()
-                .enumerate()
-                .map(move |(c, _)| (TextPoint { row: r, col: c }, usize::MAX))
-        })
+        .map(|&(row, col)| TextPoint { row, col })
         .collect();
-    let mut heap = BinaryHeap::new();
-
-    let start = TextPoint { row: 0, col: 0 };
-    let goal = TextPoint {
-        row: graph.len() - 1,
-        col: graph[0].len() - 1,
+    let corner = TextPoint {
+        row: size - 1,
+        col: size - 1,
     };
+    first_blocking(size, size, &obstacles, TextPoint { row: 0, col: 0 }, corner)
+}
 
-    // We're at `start`, with a zero cost
-    *dist.get_mut(&start).unwrap() = 0;
-    heap.push(State {
-        cost: 0,
-        position: start,
-    });
-
-    // Examine the frontier with lower cost nodes first (min-heap)
-    while let Some(State { cost, position }) = heap.pop() {
-        // Alternatively we could have continued to find all shortest paths
-        if position == goal {
-            return Some(cost);
-        }
-
-        // Important as we may have already found a better way
-        if cost > dist[&position] {
-            continue;
-        }
-
-        // For each node we can reach, see if we can find a way with
-        // a lower cost going through this node
-        for (next_cost, next_position) in find_edges(graph, position) {
-            let next = State {
-                cost: cost + next_cost,
-                position: next_position,
-            };
-
-            // If so, add it to the frontier and continue
-            if next.cost < dist[&next_position] {
-                heap.push(next);
-                // Relaxation, we have now found a better way
-                *dist.get_mut(&next_position).unwrap() = next.cost;
-            }
-        }
-        //print_map_with_costs(&graph, &dist);
-        //println!("=======");
-    }
+// The real memory space is 71x71 and part 1 drops the first kilobyte
+const SIZE: usize = 71;
+const FALLEN: usize = 1024;
 
-    // Goal not reachable
-    None
+pub struct Day18 {
+    bytes: Vec<(usize, usize)>,
+    size: usize,
+    fallen: usize,
 }
 
-fn parse(data: &str) -> Vec<(usize, usize)> {
-    data.lines()
-        .map(|line| {
-            let mut split = line.split(",");
-            // Yes this is weird but the problem uses x,y
-            let col = str::parse(split.next().unwrap()).unwrap();
-            let row = str::parse(split.next().unwrap()).unwrap();
-            (row, col)
-        })
-        .collect()
-}
+impl Day18 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        Day18::with_size(data, SIZE, FALLEN)
+    }
 
-fn build_graph(rows: usize, cols: usize, blobs: &[(usize, usize)]) -> Vec<Vec<u8>> {
-    let mut res = Vec::with_capacity(rows);
+    // The example is a smaller space, with fewer bytes dropped for part 1
+    pub fn with_size(data: &str, size: usize, fallen: usize) -> Result<Self> {
+        Ok(Day18 {
+            bytes: parse(data, size)?,
+            size,
+            fallen,
+        })
+    }
 
-    for r in 0..rows {
-        res.push(Vec::with_capacity(cols));
-        for c in 0..cols {
-            if blobs.contains(&(r, c)) {
-                res[r].push(b'#');
-            } else {
-                res[r].push(b'.');
-            }
-        }
+    fn prob1_inner(&self) -> Result<usize> {
+        let fallen = self.bytes.get(0..self.fallen).ok_or_else(|| {
+            Error::solve(format!(
+                "expected at least {} bytes, got {}",
+                self.fallen,
+                self.bytes.len()
+            ))
+        })?;
+        let g = build_graph(self.size, self.size, fallen);
+        shortest_path(&g).ok_or_else(|| Error::solve("the exit is already cut off"))
     }
 
-    res
+    fn prob2_inner(&self) -> Result<Vec<usize>> {
+        let blockage = cut_off(self.size, &self.bytes)
+            .ok_or_else(|| Error::solve("the exit is never cut off"))?;
+        // Remember these are inverted
+        Ok(vec![blockage.blocker.col, blockage.blocker.row])
+    }
 }
 
-fn print_map_with_costs(g: &Vec<Vec<u8>>, dist: &HashMap<TextPoint, usize>) {
-    for k in 0..g.len() {
-        for j in 0..g[0].len() {
-            let c = dist[&TextPoint { row: k, col: j }];
-            if g[k][j] == b'#' {
-                print!("#  ");
-            } else if c != usize::MAX {
-                print!("{:>2} ", c);
-            } else {
-                print!(".  ");
-            }
-        }
-        println!("");
+impl Problem for Day18 {
+    fn parse(data: &str) -> Result<Self> {
+        Day18::with_data(data)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use core::time;
-    use std::{fs, thread};
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner()?.into())
+    }
 
-    use super::*;
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner()?.into())
+    }
+}
+
+#[distributed_slice(PROBLEMS)]
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day18".to_owned(), factory::<Day18>);
+}
 
-    const TEST_DATA: &str = "5,4
+const TEST_DATA: &str = "5,4
 4,2
 4,5
 3,0
@@ -203,20 +167,49 @@ mod tests {
 2,0
 ";
 
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day18",
+    input: TEST_DATA,
+    part1: Some("22"),
+    part2: Some("6,1"),
+    factory: Some(|data| Ok(Box::new(Day18::with_size(data, 7, 12)?))),
+};
+
+#[cfg(test)]
+mod tests {
+    use core::time;
+    use std::{fs, thread};
+
+    use super::*;
+
     #[test]
     fn test_example_18() {
-        let bs = parse(TEST_DATA);
-        let g = build_graph(7, 7, &bs.as_slice()[0..11]);
+        let bs = parse(TEST_DATA, 7).unwrap();
+        let g = build_graph(7, 7, &bs.as_slice()[0..12]);
 
         let res = shortest_path(&g);
 
         assert_eq!(res, Some(22));
     }
 
+    #[test]
+    fn test_parse_outside() {
+        assert!(parse("6,6\n", 7).is_ok());
+        assert!(parse("7,0\n", 7).is_err());
+        assert!(parse("0,7\n", 7).is_err());
+    }
+
+    #[test]
+    fn test_start_blocked() {
+        let g = build_graph(7, 7, &[(0, 0)]);
+        assert_eq!(shortest_path(&g), None);
+    }
+
     #[test]
     fn test_actual_18() {
         let data = fs::read_to_string("data/day18.txt").unwrap();
-        let bs = parse(&data);
+        let bs = parse(&data, SIZE).unwrap();
         let g = build_graph(71, 71, &bs.as_slice()[0..1024]);
 
         let res = shortest_path(&g);
@@ -224,29 +217,27 @@ mod tests {
         assert_eq!(res, Some(270));
     }
 
+    #[test]
+    fn test_example_18_cut_off() {
+        let bs = parse(TEST_DATA, 7).unwrap();
+        let blockage = cut_off(7, &bs).unwrap();
+
+        assert_eq!(blockage.fallen, 21);
+        assert_eq!(blockage.blocker, TextPoint { row: 1, col: 6 });
+        assert_eq!(
+            blockage.last_path.last(),
+            Some(&TextPoint { row: 6, col: 6 })
+        );
+    }
+
     #[test]
     fn test_example_18_2() {
-        //let data = TEST_DATA;
         let data = fs::read_to_string("data/day18.txt").unwrap();
-        let bs = parse(&data);
-
-        let mut res = 0;
+        let bs = parse(&data, SIZE).unwrap();
 
-        for i in 1024..3000 {
-            //for i in 12..3000 {
-            //let g = build_graph(7, 7, &bs.as_slice()[0..i]);
-            if i % 250 == 0 {
-                println!("{}", i);
-            }
-            let g = build_graph(71, 71, &bs.as_slice()[0..i]);
-            let path = shortest_path(&g);
-            if path.is_none() {
-                res = i;
-                break;
-            }
-        }
+        let blockage = cut_off(71, &bs).unwrap();
 
         // Remember these are inverted
-        assert_eq!(bs[res - 1], (40, 51));
+        assert_eq!(bs[blockage.fallen - 1], (40, 51));
     }
 }
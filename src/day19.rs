@@ -1,45 +1,52 @@
 use std::{collections::HashMap, convert::identity};
 
 use itertools::{EitherOrBoth, Itertools};
-
-fn parse(data: &str) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
-    let mut split = data.split("\n\n");
-
-    let towels = split
-        .next()
-        .unwrap()
-        .split(", ")
-        .map(|s| s.as_bytes().to_vec())
-        .collect();
-
-    let patterns = split
-        .next()
-        .unwrap()
-        .lines()
-        .map(|s| s.as_bytes().to_vec())
-        .collect();
-
-    (towels, patterns)
+use linkme::distributed_slice;
+
+use crate::{
+    answer::Answer,
+    error::{Error, Result},
+    examples::{Example, EXAMPLES},
+    problem::{factory, Problem, ProblemFactory, PROBLEMS},
+};
+
+// A towel or a pattern, one colour per stripe
+type Stripes = Vec<u8>;
+
+// The towels on the first line, then a blank line, then a pattern per line
+fn parse(data: &str) -> Result<(Vec<Stripes>, Vec<Stripes>)> {
+    let (towels, patterns) = parse_as_string(data)?;
+    Ok((to_bytes(&towels), to_bytes(&patterns)))
 }
 
-fn parse_as_string(data: &str) -> (Vec<String>, Vec<String>) {
-    let mut split = data.split("\n\n");
+fn to_bytes(v: &[String]) -> Vec<Stripes> {
+    v.iter().map(|s| s.as_bytes().to_vec()).collect()
+}
 
-    let towels = split
-        .next()
-        .unwrap()
+fn parse_as_string(data: &str) -> Result<(Vec<String>, Vec<String>)> {
+    let Some((towels, patterns)) = data.split_once("\n\n") else {
+        return Err(Error::parse(
+            data,
+            data,
+            "expected the towels, a blank line and then the patterns",
+        ));
+    };
+
+    let towels = towels
+        .trim_end()
         .split(", ")
-        .map(|s| s.to_string())
-        .collect();
+        .map(|s| {
+            if s.is_empty() {
+                Err(Error::parse(data, s, "expected a towel"))
+            } else {
+                Ok(s.to_string())
+            }
+        })
+        .collect::<Result<_>>()?;
 
-    let patterns = split
-        .next()
-        .unwrap()
-        .lines()
-        .map(|s| s.to_string())
-        .collect();
+    let patterns = patterns.lines().map(|s| s.to_string()).collect();
 
-    (towels, patterns)
+    Ok((towels, patterns))
 }
 
 fn search(towels: &Vec<Vec<u8>>, pattern: &Vec<u8>, path: &Vec<Vec<u8>>) -> Option<Vec<Vec<u8>>> {
@@ -97,14 +104,59 @@ fn search_combos(
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use core::time;
-    use std::{fs, thread};
+pub struct Day19 {
+    towels: Vec<Stripes>,
+    patterns: Vec<Stripes>,
+}
 
-    use super::*;
+impl Day19 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        let (towels, patterns) = parse(data)?;
+        Ok(Day19 { towels, patterns })
+    }
+
+    fn prob1_inner(&self) -> usize {
+        self.patterns
+            .iter()
+            .filter_map(|p| search(&self.towels, p, &vec![]))
+            .count()
+    }
 
-    const TEST_DATA: &str = "r, wr, b, g, bwu, rb, gb, br
+    fn prob2_inner(&self) -> usize {
+        let to_string = |v: &[Stripes]| {
+            v.iter()
+                .map(|s| String::from_utf8_lossy(s).into_owned())
+                .collect::<Vec<_>>()
+        };
+        let towels = to_string(&self.towels);
+        let mut cache = HashMap::new();
+        to_string(&self.patterns)
+            .into_iter()
+            .map(|p| search_combos(&towels, p, &mut cache))
+            .sum()
+    }
+}
+
+impl Problem for Day19 {
+    fn parse(data: &str) -> Result<Self> {
+        Day19::with_data(data)
+    }
+
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
+    }
+
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner().into())
+    }
+}
+
+#[distributed_slice(PROBLEMS)]
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day19".to_owned(), factory::<Day19>);
+}
+
+const TEST_DATA: &str = "r, wr, b, g, bwu, rb, gb, br
 
 brwrr
 bggr
@@ -115,9 +167,25 @@ bwurrg
 brgr
 bbrgwb";
 
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day19",
+    input: TEST_DATA,
+    part1: Some("6"),
+    part2: Some("16"),
+    factory: None,
+};
+
+#[cfg(test)]
+mod tests {
+    use core::time;
+    use std::{fs, thread};
+
+    use super::*;
+
     #[test]
     fn test_parse() {
-        let (towels, _patterns) = parse(TEST_DATA);
+        let (towels, _patterns) = parse(TEST_DATA).unwrap();
 
         assert_eq!(
             towels,
@@ -136,7 +204,7 @@ bbrgwb";
 
     #[test]
     fn test_search_one() {
-        let (towels, patterns) = parse(TEST_DATA);
+        let (towels, patterns) = parse(TEST_DATA).unwrap();
 
         let res = search(&towels, &patterns[0], &vec![]);
 
@@ -153,7 +221,7 @@ bbrgwb";
 
     #[test]
     fn test_search_all() {
-        let (towels, patterns) = parse(TEST_DATA);
+        let (towels, patterns) = parse(TEST_DATA).unwrap();
 
         let res = patterns
             .iter()
@@ -166,7 +234,7 @@ bbrgwb";
     #[test]
     fn test_problem_day19() {
         let data = fs::read_to_string("data/day19.txt").unwrap();
-        let (towels, patterns) = parse(&data);
+        let (towels, patterns) = parse(&data).unwrap();
 
         let res = patterns
             .iter()
@@ -178,7 +246,7 @@ bbrgwb";
 
     #[test]
     fn test_search_combos() {
-        let (towels, patterns) = parse_as_string(TEST_DATA);
+        let (towels, patterns) = parse_as_string(TEST_DATA).unwrap();
 
         let mut cache = HashMap::new();
 
@@ -196,7 +264,7 @@ bbrgwb";
     #[test]
     fn test_problem_day19_part2() {
         let data = fs::read_to_string("data/day19.txt").unwrap();
-        let (towels, patterns) = parse_as_string(&data);
+        let (towels, patterns) = parse_as_string(&data).unwrap();
 
         let mut cache = HashMap::new();
 
@@ -207,4 +275,10 @@ bbrgwb";
 
         assert_eq!(res, 643685981770598);
     }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("r, wr, b").is_err());
+        assert!(parse("r, , b\n\nrwb").is_err());
+    }
 }
@@ -1,16 +1,17 @@
-use std::{collections::HashMap, convert::identity, fs};
+use std::{collections::HashMap, convert::identity};
 
 use linkme::distributed_slice;
 
-use crate::problem::{Problem, PROBLEMS};
+use crate::{
+    answer::Answer,
+    error::{parse_at, Result},
+    examples::{Example, EXAMPLES},
+    problem::{factory, Problem, ProblemFactory, PROBLEMS},
+};
 
-fn parse(s: &str) -> Vec<Vec<usize>> {
+fn parse(s: &str) -> Result<Vec<Vec<usize>>> {
     s.lines()
-        .map(|line| {
-            line.split_whitespace()
-                .map(|ns| ns.parse().unwrap())
-                .collect()
-        })
+        .map(|line| line.split_whitespace().map(|ns| parse_at(s, ns)).collect())
         .collect()
 }
 
@@ -85,20 +86,16 @@ struct Day2 {
 }
 
 impl Day2 {
-    pub fn new() -> Self {
-        let data = fs::read_to_string("data/day2.txt").unwrap();
-        Day2::with_data(&data)
-    }
-    fn with_data(s: &str) -> Self {
-        Day2 { data: parse(s) }
+    fn with_data(s: &str) -> Result<Self> {
+        Ok(Day2 { data: parse(s)? })
     }
-    fn prob1_inner(&mut self) -> usize {
+    fn prob1_inner(&self) -> usize {
         self.data
             .iter()
             .map(|v| if is_safe_report(v).into() { 1 } else { 0 })
             .sum()
     }
-    fn prob2_inner(&mut self) -> usize {
+    fn prob2_inner(&self) -> usize {
         self.data
             .iter()
             .map(|v| match is_safe_report(&v) {
@@ -123,34 +120,49 @@ impl Day2 {
 }
 
 impl Problem for Day2 {
-    fn prob1(&mut self) -> Box<dyn std::fmt::Display> {
-        Box::new(self.prob1_inner())
+    fn parse(data: &str) -> Result<Self> {
+        Day2::with_data(data)
+    }
+
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
     }
 
-    fn prob2(&mut self) -> Box<dyn std::fmt::Display> {
-        Box::new(self.prob2_inner())
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner().into())
     }
 }
 
 #[distributed_slice(PROBLEMS)]
-fn register_day(p: &mut HashMap<String, fn() -> Box<dyn Problem>>) {
-    p.insert("day2".to_owned(), || Box::new(Day2::new()));
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day2".to_owned(), factory::<Day2>);
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    const TEST_DATA: &str = "7 6 4 2 1
+const TEST_DATA: &str = "7 6 4 2 1
         1 2 7 8 9
         9 7 6 2 1
         1 3 2 4 5
         8 6 4 4 1
         1 3 6 7 9";
 
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day2",
+    input: TEST_DATA,
+    part1: Some("2"),
+    part2: Some("4"),
+    factory: None,
+};
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
     #[test]
     fn test_basic_parse() {
-        let res = parse("1 2\n3 4");
+        let res = parse("1 2\n3 4").unwrap();
 
         assert_eq!(res, vec![vec![1, 2], vec![3, 4]]);
     }
@@ -182,7 +194,7 @@ mod tests {
 
     #[test]
     fn test_example() {
-        let data = parse(TEST_DATA);
+        let data = parse(TEST_DATA).unwrap();
         assert_eq!(
             data.into_iter()
                 .map(|v| is_safe_report(&v).into())
@@ -193,19 +205,20 @@ mod tests {
 
     #[test]
     fn test_example_prob1() {
-        let mut day2 = Day2::with_data(TEST_DATA);
+        let day2 = Day2::with_data(TEST_DATA).unwrap();
         assert_eq!(day2.prob1_inner(), 2);
     }
 
     #[test]
     fn test_prob2() {
-        let mut day2 = Day2::with_data(TEST_DATA);
+        let day2 = Day2::with_data(TEST_DATA).unwrap();
         assert_eq!(day2.prob2_inner(), 4);
     }
 
     #[test]
     fn test_day2() {
-        let mut day2 = Day2::new();
+        let data = fs::read_to_string("data/day2.txt").unwrap();
+        let day2 = Day2::with_data(&data).unwrap();
         assert_eq!(day2.prob1_inner(), 282);
         assert_eq!(day2.prob2_inner(), 349);
     }
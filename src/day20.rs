@@ -3,7 +3,7 @@ mod tests {
     use core::time;
     use std::{fs, thread};
 
-    use crate::day4::char_matrix;
+    use crate::point::char_matrix;
 
     use super::*;
 
@@ -1,9 +1,14 @@
-use std::{collections::HashMap, fs};
+use std::collections::HashMap;
 
 use linkme::distributed_slice;
 use regex::Regex;
 
-use crate::problem::{Problem, PROBLEMS};
+use crate::{
+    answer::Answer,
+    error::{Error, Result},
+    examples::{Example, EXAMPLES},
+    problem::{factory, variant_name, Problem, ProblemFactory, PROBLEMS},
+};
 
 fn get_pairs(s: &str) -> Vec<(usize, usize)> {
     let re = Regex::new(r"mul\(([0-9]+),([0-9]+)\)").unwrap();
@@ -43,35 +48,38 @@ pub struct Day3 {
 }
 
 impl Day3 {
-    pub fn new() -> Self {
-        let data = fs::read_to_string("data/day3.txt").unwrap();
+    pub fn with_data(data: &str) -> Self {
         Day3 {
             data: data.to_owned(),
         }
     }
-    fn prob1_inner(&mut self) -> usize {
+    fn prob1_inner(&self) -> usize {
         let pairs = get_pairs(&self.data);
         pairs.iter().map(|(l, r)| l * r).sum()
     }
-    fn prob2_inner(&mut self) -> usize {
+    fn prob2_inner(&self) -> usize {
         let pairs = get_pairs_stateful(&self.data);
         pairs.iter().map(|(l, r)| l * r).sum()
     }
 }
 
 impl Problem for Day3 {
-    fn prob1(&mut self) -> Box<dyn std::fmt::Display> {
-        Box::new(self.prob1_inner())
+    fn parse(data: &str) -> Result<Self> {
+        Ok(Day3::with_data(data))
+    }
+
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
     }
 
-    fn prob2(&mut self) -> Box<dyn std::fmt::Display> {
-        Box::new(self.prob2_inner())
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner().into())
     }
 }
 
 #[distributed_slice(PROBLEMS)]
-fn register_day(p: &mut HashMap<String, fn() -> Box<dyn Problem>>) {
-    p.insert("day3".to_owned(), || Box::new(Day3::new()));
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day3".to_owned(), factory::<Day3>);
 }
 
 use chumsky::prelude::*;
@@ -86,7 +94,10 @@ enum Expr {
 }
 
 fn mul() -> impl Parser<char, Expr, Error = Simple<char>> {
-    let int = text::int(10).map(|s: String| s.parse().unwrap());
+    let int = text::int(10).try_map(|s: String, span| {
+        s.parse()
+            .map_err(|e| Simple::custom(span, format!("{s} is too big: {e}")))
+    });
     just("mul(")
         .ignore_then(int)
         .then_ignore(just(","))
@@ -95,81 +106,133 @@ fn mul() -> impl Parser<char, Expr, Error = Simple<char>> {
         .map(|(l, r)| Expr::Mul(l, r))
 }
 
+// Everything from a don't to the next do, or to the end if there isn't one
 fn dont_do() -> impl Parser<char, Expr, Error = Simple<char>> {
-    let do_end = just("don't").not().rewind().then(just("do"));
+    let do_end = just("don't").not().rewind().then(just("do")).ignored();
+    let end_of_dont = do_end.or(end());
     do_end
-        .clone()
         .not()
         .repeated()
         .ignored()
         .map(|_| Expr::Null)
-        .delimited_by(just("don't"), do_end)
+        .delimited_by(just("don't"), end_of_dont)
+}
+
+fn seq(v: Vec<Expr>) -> Expr {
+    Expr::Seq(
+        v.into_iter()
+            .filter(|e| !matches!(e, Expr::Null))
+            .map(Box::new)
+            .collect(),
+    )
+}
+
+// Every mul, whether or not it's been switched off
+fn all_muls() -> impl Parser<char, Expr, Error = Simple<char>> {
+    let junk = mul().not().ignored().map(|_| Expr::Null);
+    choice((mul(), junk)).repeated().map(seq)
 }
 
 fn parser() -> impl Parser<char, Expr, Error = Simple<char>> {
     let junk = mul().not().ignored().map(|_| Expr::Null);
-    choice((dont_do(), mul(), junk)).repeated().map(|v| {
-        Expr::Seq(
-            v.into_iter()
-                .filter(|e| !matches!(e, Expr::Null))
-                .map(Box::new)
-                .collect(),
-        )
-    })
+    choice((dont_do(), mul(), junk)).repeated().map(seq)
+}
+
+// Chumsky's spans count chars, but errors point at a slice of the input
+fn parse_error(data: &str, e: Simple<char>) -> Error {
+    let byte = |c| data.char_indices().nth(c).map_or(data.len(), |(i, _)| i);
+    let span = e.span();
+    Error::parse(data, &data[byte(span.start)..byte(span.end)], e)
+}
+
+fn sum_muls(data: &str, p: impl Parser<char, Expr, Error = Simple<char>>) -> Result<u64> {
+    let expr = p
+        .parse(data)
+        .map_err(|errs| match errs.into_iter().next() {
+            Some(e) => parse_error(data, e),
+            None => Error::parse(data, data, "couldn't parse the program"),
+        })?;
+
+    match expr {
+        Expr::Seq(v) => v
+            .iter()
+            .map(|e| match e.as_ref() {
+                Expr::Mul(a, b) => Ok(a * b),
+                e => Err(Error::solve(format!("expected only muls, got {e:?}"))),
+            })
+            .sum(),
+        e => Err(Error::solve(format!("expected a list of muls, got {e:?}"))),
+    }
 }
 
 #[derive(Default)]
-pub struct Day3Chumsky {}
+pub struct Day3Chumsky {
+    data: String,
+}
 
 impl Day3Chumsky {
-    pub fn new() -> Self {
-        Day3Chumsky {}
+    pub fn with_data(data: &str) -> Self {
+        Day3Chumsky {
+            data: data.to_owned(),
+        }
     }
-    fn prob1_inner(&mut self) -> String {
-        "Not Implemented".to_string()
+    fn prob1_inner(&self) -> Result<u64> {
+        sum_muls(&self.data, all_muls())
     }
 
-    fn prob2_inner(&mut self) -> u64 {
-        let data = fs::read_to_string("data/day3.txt").unwrap();
-        let p = parser();
-        let res = p.parse(data).unwrap();
-        match res {
-            Expr::Seq(v) => v
-                .iter()
-                .map(|mul| match mul.as_ref() {
-                    Expr::Mul(a, b) => a * b,
-                    Expr::Null => 0,
-                    _ => panic!("Shouldn't have anything else"),
-                })
-                .sum(),
-            _ => panic!("Shouldn't have anything else"),
-        }
+    fn prob2_inner(&self) -> Result<u64> {
+        sum_muls(&self.data, parser())
     }
 }
 
 impl Problem for Day3Chumsky {
-    fn prob1(&mut self) -> Box<dyn std::fmt::Display> {
-        Box::new(self.prob1_inner())
+    fn parse(data: &str) -> Result<Self> {
+        Ok(Day3Chumsky::with_data(data))
+    }
+
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner()?.into())
     }
 
-    fn prob2(&mut self) -> Box<dyn std::fmt::Display> {
-        Box::new(self.prob2_inner())
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner()?.into())
     }
 }
 
 #[distributed_slice(PROBLEMS)]
-fn register_day_chumsky(p: &mut HashMap<String, fn() -> Box<dyn Problem>>) {
-    p.insert("day3-chumsky".to_owned(), || Box::new(Day3Chumsky::new()));
+fn register_day_chumsky(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert(variant_name("day3", "chumsky"), factory::<Day3Chumsky>);
 }
 
+const TEST_DATA: &str = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
+
+const TEST_DATA_2: &str =
+    "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day3",
+    input: TEST_DATA,
+    part1: Some("161"),
+    part2: None,
+    factory: None,
+};
+
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE_2: Example = Example {
+    day: "day3",
+    input: TEST_DATA_2,
+    part1: Some("161"),
+    part2: Some("48"),
+    factory: None,
+};
+
 #[cfg(test)]
 mod tests {
+    use std::fs;
 
     use super::*;
 
-    const TEST_DATA: &str =
-        "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
-
     #[test]
     fn test_basics_parse() {
         let pairs = get_pairs(TEST_DATA);
@@ -178,9 +241,6 @@ mod tests {
         assert_eq!(res, 161);
     }
 
-    const TEST_DATA_2: &str =
-        "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
-
     #[test]
     fn test_prob2() {
         let pairs = get_pairs_stateful(TEST_DATA_2);
@@ -189,7 +249,8 @@ mod tests {
 
     #[test]
     fn test_prob() {
-        let mut day3 = Day3::new();
+        let data = fs::read_to_string("data/day3.txt").unwrap();
+        let day3 = Day3::with_data(&data);
         assert_eq!(day3.prob1_inner(), 183380722);
         assert_eq!(day3.prob2_inner(), 82733683);
     }
@@ -263,9 +324,29 @@ mod tests {
         );
     }
 
-    //#[test]
-    //fn test_prob_chumsky() {
-    //    let mut day3 = Day3Chumsky::new();
-    //    assert_eq!(day3.prob2_inner(), 82733683);
-    //}
+    #[test]
+    fn test_dont_to_the_end() {
+        let p = parser();
+        assert_eq!(
+            p.parse("mul(2,3)don't()mul(4,5)"),
+            Ok(Expr::Seq(vec![Box::new(Expr::Mul(2, 3))]))
+        );
+    }
+
+    #[test]
+    fn test_prob_chumsky() {
+        let data = fs::read_to_string("data/day3.txt").unwrap();
+        let day3 = Day3Chumsky::with_data(&data);
+        assert_eq!(day3.prob1_inner(), Ok(183380722));
+        assert_eq!(day3.prob2_inner(), Ok(82733683));
+    }
+
+    #[test]
+    fn test_sum_muls_errors() {
+        assert_eq!(sum_muls("mul(2,3)xmul(4,5)", all_muls()), Ok(26));
+        // Only muls can be multiplied out
+        assert!(sum_muls("12", num_parser()).is_err());
+        // Anything else has to parse
+        assert!(sum_muls("12", mul()).is_err());
+    }
 }
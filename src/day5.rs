@@ -1,12 +1,16 @@
 use std::{
     collections::{HashMap, HashSet},
     convert::identity,
-    fs,
 };
 
 use linkme::distributed_slice;
 
-use crate::problem::{Problem, PROBLEMS};
+use crate::{
+    answer::Answer,
+    error::{parse_at, Error, Result},
+    examples::{Example, EXAMPLES},
+    problem::{factory, Problem, ProblemFactory, PROBLEMS},
+};
 
 // Graph implementation from:
 // https://smallcultfollowing.com/babysteps/blog/2015/04/06/modeling-graphs-in-rust-using-vector-indices/
@@ -104,27 +108,30 @@ impl<'graph> Iterator for Successors<'graph> {
     }
 }
 
-fn parse_rules(rules: &str) -> Graph {
+// `data` is the whole input, so errors can say where in it things went wrong
+fn parse_rules(data: &str, rules: &str) -> Result<Graph> {
     let mut g = Graph::default();
 
     for line in rules.lines() {
-        let mut ns = line.split("|");
-
-        let s: NodeValue = ns.next().unwrap().parse().unwrap();
-        let t: NodeValue = ns.next().unwrap().parse().unwrap();
+        // Exactly one `|`, so `1|2|3` isn't read as `1|2`
+        let Some((s, t)) = line.split_once('|').filter(|(_, t)| !t.contains('|')) else {
+            return Err(Error::parse(data, line, "expected a rule like `47|53`"));
+        };
+        let s: NodeValue = parse_at(data, s)?;
+        let t: NodeValue = parse_at(data, t)?;
 
         g.add_node(s);
         g.add_node(t);
         g.add_edge_by_value(s, t);
     }
 
-    g
+    Ok(g)
 }
 
-fn parse_updates(updates: &str) -> Vec<Vec<NodeValue>> {
+fn parse_updates(data: &str, updates: &str) -> Result<Vec<Vec<NodeValue>>> {
     updates
         .lines()
-        .map(|line| line.split(",").map(|n| str::parse(n).unwrap()).collect())
+        .map(|line| line.split(",").map(|n| parse_at(data, n)).collect())
         .collect()
 }
 
@@ -229,64 +236,56 @@ fn topological_sort(g: &Graph, nodes: &[NodeValue]) -> Vec<NodeValue> {
     l.into_iter().map(|n| g.nodes[n.0].value).collect()
 }
 
-#[derive(Default)]
-pub struct Day5Part1 {
-    rules: Graph,
+// Updates already in order, and the ones that aren't
+fn partition(
+    rules: &Graph,
     updates: Vec<Vec<NodeValue>>,
+) -> (Vec<Vec<NodeValue>>, Vec<Vec<NodeValue>>) {
+    updates.into_iter().partition(|values| {
+        let (g, values) = extend_graph_with_values(rules, values);
+        let filter: HashSet<NodeIndex> =
+            HashSet::from_iter(values.iter().map(|v| *g.index.get(&v).unwrap()));
+        !contains_cycle(&g, &filter)
+    })
 }
 
 #[derive(Default)]
-pub struct Day5Part2 {
+pub struct Day5 {
     rules: Graph,
+    // Both parts only care which updates are already in order, so that's
+    // worked out once up front
+    valid: Vec<Vec<NodeValue>>,
     invalid: Vec<Vec<NodeValue>>,
 }
 
-impl Day5Part1 {
-    pub fn new() -> Self {
-        let data = fs::read_to_string("data/day5.txt").unwrap();
-        Day5Part1::with_data(&data)
-    }
-
-    pub fn with_data(data: &str) -> Self {
-        let parsed_data: Vec<_> = data.split("\n\n").collect();
-
-        Day5Part1 {
-            rules: parse_rules(parsed_data[0]),
-            updates: parse_updates(parsed_data[1]),
-        }
+impl Day5 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        let Some((rules, updates)) = data.split_once("\n\n") else {
+            return Err(Error::parse(
+                data,
+                data,
+                "expected rules and updates separated by a blank line",
+            ));
+        };
+
+        let rules = parse_rules(data, rules)?;
+        let (valid, invalid) = partition(&rules, parse_updates(data, updates)?);
+        Ok(Day5 {
+            rules,
+            valid,
+            invalid,
+        })
     }
 
-    fn prob1_inner(self) -> (Day5Part2, usize) {
-        let (valid, invalid): (Vec<_>, Vec<_>) = self
-            .updates
+    fn prob1_inner(&self) -> usize {
+        self.valid
             .iter()
-            .map(|values| extend_graph_with_values(&self.rules, values))
-            .partition(|(g, values)| {
-                let filter: HashSet<NodeIndex> =
-                    HashSet::from_iter(values.iter().map(|v| *g.index.get(&v).unwrap()));
-                !contains_cycle(&g, &filter)
-            });
-
-        let invalid = invalid.iter().map(|(_, v)| v.to_vec()).collect();
-
-        let res = valid
-            .iter()
-            //.inspect(|(_, values)| println!("{:?}", values))
-            .map(|(_, values)| values[values.len() / 2])
+            //.inspect(|values| println!("{:?}", values))
+            .map(|values| values[values.len() / 2])
             //.inspect(|value| println!("{:?}", value))
-            .sum();
-
-        (
-            Day5Part2 {
-                rules: self.rules,
-                invalid,
-            },
-            res,
-        )
+            .sum()
     }
-}
 
-impl Day5Part2 {
     fn prob2_inner(&self) -> usize {
         self.invalid
             .iter()
@@ -296,52 +295,26 @@ impl Day5Part2 {
     }
 }
 
-#[derive(Default)]
-struct Day5 {
-    part1: Option<Day5Part1>,
-    part2: Option<Day5Part2>,
-}
-
-impl Day5 {
-    fn new() -> Self {
-        Day5 {
-            part1: Some(Day5Part1::new()),
-            part2: None,
-        }
-    }
-
-    fn prob1_inner(&mut self) -> usize {
-        let part1 = self.part1.take().unwrap();
-        let (part2, res) = part1.prob1_inner();
-        self.part2 = Some(part2);
-        res
-    }
-
-    fn prob2_inner(&mut self) -> usize {
-        self.part2.as_ref().unwrap().prob2_inner()
+impl Problem for Day5 {
+    fn parse(data: &str) -> Result<Self> {
+        Day5::with_data(data)
     }
-}
 
-impl Problem for Day5 {
-    fn prob1(&mut self) -> Box<dyn std::fmt::Display> {
-        Box::new(self.prob1_inner())
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
     }
 
-    fn prob2(&mut self) -> Box<dyn std::fmt::Display> {
-        Box::new(self.prob2_inner())
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner().into())
     }
 }
 
 #[distributed_slice(PROBLEMS)]
-fn register_day(p: &mut HashMap<String, fn() -> Box<dyn Problem>>) {
-    p.insert("day5".to_owned(), || Box::new(Day5::new()));
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day5".to_owned(), factory::<Day5>);
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    const TEST_RULE_DATA: &str = "47|53
+const TEST_DATA: &str = "47|53
 97|13
 97|61
 97|47
@@ -361,15 +334,39 @@ mod tests {
 75|61
 47|29
 75|13
-53|13";
+53|13
 
-    const TEST_UPDATE_DATA: &str = "75,47,61,53,29
+75,47,61,53,29
 97,61,53,29,13
 75,29,13
 75,97,47,61,53
 61,13,29
 97,13,75,29,47";
 
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day5",
+    input: TEST_DATA,
+    part1: Some("143"),
+    part2: Some("123"),
+    factory: None,
+};
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::error::ErrorKind;
+
+    fn test_rules() -> &'static str {
+        TEST_DATA.split_once("\n\n").unwrap().0
+    }
+
+    fn test_updates() -> &'static str {
+        TEST_DATA.split_once("\n\n").unwrap().1
+    }
+
     #[test]
     fn test_basic_graph() {
         let mut g = Graph::default();
@@ -395,9 +392,19 @@ mod tests {
         assert_eq!(g.successors(NodeIndex(1)).collect::<Vec<_>>(), vec![]);
     }
 
+    #[test]
+    fn test_parse_bad_rules() {
+        for rules in ["47|53|61", "47", "47|"] {
+            assert!(matches!(
+                parse_rules(rules, rules).map_err(|e| e.kind),
+                Err(ErrorKind::Parse { .. })
+            ));
+        }
+    }
+
     #[test]
     fn test_parse_rules() {
-        let g = parse_rules(TEST_RULE_DATA);
+        let g = parse_rules(test_rules(), test_rules()).unwrap();
 
         let s = HashSet::from_iter(g.successors(NodeIndex(0)));
 
@@ -435,9 +442,9 @@ mod tests {
 
     #[test]
     fn test_problem1_example_only_cycle() {
-        let g = parse_rules(TEST_RULE_DATA);
+        let g = parse_rules(test_rules(), test_rules()).unwrap();
 
-        let data = parse_updates(TEST_UPDATE_DATA);
+        let data = parse_updates(test_updates(), test_updates()).unwrap();
 
         let res: Vec<_> = data
             .iter()
@@ -453,14 +460,13 @@ mod tests {
 
     #[test]
     fn test_problem1_whole() {
-        let data = TEST_RULE_DATA.to_owned() + "\n\n" + TEST_UPDATE_DATA;
-        let day5 = Day5Part1::with_data(&data);
-        assert_eq!(day5.prob1_inner().1, 143);
+        let day5 = Day5::with_data(TEST_DATA).unwrap();
+        assert_eq!(day5.prob1_inner(), 143);
     }
 
     #[test]
     fn test_topological_sort() {
-        let g = parse_rules(TEST_RULE_DATA);
+        let g = parse_rules(test_rules(), test_rules()).unwrap();
         let res = topological_sort(&g, &vec![75, 97, 47, 61, 53]);
         assert_eq!(res, vec![97, 75, 47, 61, 53]);
         let res = topological_sort(&g, &vec![61, 13, 29]);
@@ -471,16 +477,22 @@ mod tests {
 
     #[test]
     fn test_problem2_whole() {
-        let data = TEST_RULE_DATA.to_owned() + "\n\n" + TEST_UPDATE_DATA;
-        let day5 = Day5Part1::with_data(&data).prob1_inner().0;
+        let day5 = Day5::with_data(TEST_DATA).unwrap();
         assert_eq!(day5.prob2_inner(), 123);
     }
 
+    #[test]
+    fn test_part2_on_its_own() {
+        let day5 = Day5::with_data(TEST_DATA).unwrap();
+        assert_eq!(day5.prob2().unwrap(), Answer::Int(123));
+        assert_eq!(day5.prob1().unwrap(), Answer::Int(143));
+    }
+
     #[test]
     fn test_actual_problem() {
-        let day5 = Day5Part1::new();
-        let (day5part2, res) = day5.prob1_inner();
-        assert_eq!(res, 4959);
-        assert_eq!(day5part2.prob2_inner(), 4655);
+        let data = fs::read_to_string("data/day5.txt").unwrap();
+        let day5 = Day5::with_data(&data).unwrap();
+        assert_eq!(day5.prob1_inner(), 4959);
+        assert_eq!(day5.prob2_inner(), 4655);
     }
 }
@@ -1,90 +1,43 @@
-use day4::{explode_point, extract_string_from_vector, find_char_in_puzzle, TextPoint};
-use enum_iterator::{all, Sequence};
-
-use crate::day4::{self, char_matrix};
-
-#[derive(Copy, Clone, Debug, PartialEq, Sequence, Hash, Eq)]
-pub enum Direction {
-    N,
-    NE,
-    E,
-    SE,
-    S,
-    SW,
-    W,
-    NW,
-}
+use std::collections::{HashMap, HashSet};
 
-impl Direction {
-    pub fn rotate_right(&self) -> Self {
-        match self {
-            Direction::N => Direction::E,
-            Direction::NE => Direction::SE,
-            Direction::E => Direction::S,
-            Direction::SE => Direction::SW,
-            Direction::S => Direction::W,
-            Direction::SW => Direction::NW,
-            Direction::W => Direction::N,
-            Direction::NW => Direction::NE,
-        }
-    }
-    pub fn rotate_left(&self) -> Self {
-        match self {
-            Direction::N => Direction::W,
-            Direction::NW => Direction::SW,
-            Direction::W => Direction::S,
-            Direction::SW => Direction::SE,
-            Direction::S => Direction::E,
-            Direction::SE => Direction::NE,
-            Direction::E => Direction::N,
-            Direction::NE => Direction::NW,
-        }
-    }
-}
-
-pub fn explode_point_with_directions(
-    p: TextPoint,
-    corner: TextPoint,
-    length: usize,
-) -> Vec<(Direction, Vec<TextPoint>)> {
-    let v = explode_point(p, corner, length);
+use linkme::distributed_slice;
 
-    all::<Direction>().zip(v).collect()
-}
+use crate::{
+    answer::Answer,
+    cell::Terrain,
+    direction::Cardinal,
+    error::{Error, Result},
+    examples::{Example, EXAMPLES},
+    grid::Grid,
+    point::TextPoint,
+    problem::{factory, Problem, ProblemFactory, PROBLEMS},
+};
 
 #[derive(Debug, Clone)]
 struct GuardMap {
-    guard_map: Vec<Vec<u8>>,
-    guard: (Direction, Option<TextPoint>),
+    guard_map: Grid<Terrain>,
+    guard: (Cardinal, Option<TextPoint>),
 }
 
 impl GuardMap {
     fn tick(&mut self) -> Option<TextPoint> {
-        let corner = TextPoint {
-            row: self.guard_map.len() - 1,
-            col: self.guard_map[0].len() - 1,
-        };
+        let corner = self.guard_map.corner();
 
         if let Some(guard_point) = self.guard.1 {
-            let all_moves = explode_point_with_directions(guard_point, corner, 2);
-
             // I kind of wanted to make this recursive, but decided to loop instead
             for _ in 0..4 {
-                let v = all_moves
-                    .iter()
-                    .filter(|(d, _)| d == &self.guard.0)
-                    .nth(0)
-                    .unwrap();
-                let s = extract_string_from_vector(&self.guard_map, &v.1);
-                if s == "." {
-                    self.guard.1 = None;
-                    break;
+                match guard_point.step(self.guard.0, corner) {
+                    // Off the edge of the map
+                    None => {
+                        self.guard.1 = None;
+                        break;
+                    }
+                    Some(next) if self.guard_map[next] == Terrain::Open => {
+                        self.guard.1 = Some(next);
+                        break;
+                    }
+                    Some(_) => self.guard.0 = self.guard.0.rotate_right(),
                 }
-                if s == ".." {
-                    self.guard.1 = Some(v.1[1]);
-                    break;
-                }
-                self.guard.0 = self.guard.0.rotate_right();
             }
         }
 
@@ -92,30 +45,121 @@ impl GuardMap {
     }
 }
 
-fn parse(data: &str) -> GuardMap {
-    let data: Vec<Vec<u8>> = char_matrix(data);
-
-    let g = find_char_in_puzzle(&data, b'^')[0];
+fn parse(input: &str) -> Result<GuardMap> {
+    // The guard is an arrow pointing the way they're facing
+    let arrows = [b'^', b'>', b'v', b'<'].map(|c| (c, Terrain::Open));
+    let (data, markers) = Grid::parse_cells(input, &arrows)?;
 
-    let data = replace_char_in_puzzle(&data, g, b'.');
+    let guards: Vec<(Cardinal, TextPoint)> = arrows
+        .iter()
+        .filter_map(|(c, _)| Some((Cardinal::from_char(*c as char)?, markers.all(*c))))
+        .flat_map(|(d, ps)| ps.iter().map(move |p| (d, *p)))
+        .collect();
+    let (d, g) = match guards[..] {
+        [guard] => guard,
+        [] => {
+            return Err(Error::parse(
+                input,
+                input,
+                "there is no guard (`^`, `>`, `v` or `<`) on the map",
+            ))
+        }
+        [_, (_, second), ..] => {
+            let line = input.lines().nth(second.row).unwrap_or(input);
+            return Err(Error::parse(input, line, "there is more than one guard"));
+        }
+    };
 
-    GuardMap {
+    Ok(GuardMap {
         guard_map: data,
-        guard: (Direction::N, Some(g)),
-    }
+        guard: (d, Some(g)),
+    })
 }
 
-fn replace_char_in_puzzle(puzzle: &Vec<Vec<u8>>, p: TextPoint, new: u8) -> Vec<Vec<u8>> {
-    let mut puzzle = puzzle.clone().to_vec();
-    puzzle[p.row][p.col] = new;
+fn with_obstruction(puzzle: &Grid<Terrain>, p: TextPoint) -> Grid<Terrain> {
+    let mut puzzle = puzzle.clone();
+    puzzle[p] = Terrain::Wall;
     puzzle
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn patrol(guard_map: &GuardMap) -> HashSet<TextPoint> {
+    let mut guard_map = guard_map.clone();
+    let mut visited = HashSet::new();
+
+    while let Some(p) = guard_map.guard.1 {
+        visited.insert(p);
+        guard_map.tick();
+    }
+
+    visited
+}
+
+fn is_loop(guard_map: &GuardMap) -> bool {
+    let mut guard_map = guard_map.clone();
+    let mut visited: HashSet<(Cardinal, TextPoint)> = HashSet::new();
+
+    while let Some(p) = guard_map.guard.1 {
+        if !visited.insert((guard_map.guard.0, p)) {
+            return true;
+        }
+        guard_map.tick();
+    }
+
+    false
+}
+
+pub struct Day6 {
+    guard_map: GuardMap,
+}
+
+impl Day6 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        Ok(Day6 {
+            guard_map: parse(data)?,
+        })
+    }
+
+    fn prob1_inner(&self) -> usize {
+        patrol(&self.guard_map).len()
+    }
+
+    fn prob2_inner(&self) -> usize {
+        let start = self.guard_map.guard.1.unwrap();
 
-    const TEST_DATA: &str = "....#.....
+        // Only points on the original route can change where the guard goes
+        patrol(&self.guard_map)
+            .into_iter()
+            .filter(|o| *o != start)
+            .filter(|o| {
+                is_loop(&GuardMap {
+                    guard_map: with_obstruction(&self.guard_map.guard_map, *o),
+                    guard: self.guard_map.guard,
+                })
+            })
+            .count()
+    }
+}
+
+impl Problem for Day6 {
+    fn parse(data: &str) -> Result<Self> {
+        Day6::with_data(data)
+    }
+
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
+    }
+
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner().into())
+    }
+}
+
+#[distributed_slice(PROBLEMS)]
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day6".to_owned(), factory::<Day6>);
+}
+
+const TEST_DATA: &str = "....#.....
 .........#
 ..........
 ..#.......
@@ -126,43 +170,81 @@ mod tests {
 #.........
 ......#...";
 
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day6",
+    input: TEST_DATA,
+    part1: Some("41"),
+    part2: Some("6"),
+    factory: None,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
-    fn test_explode_with_directions() {
-        let res = explode_point_with_directions(
-            TextPoint { row: 3, col: 2 },
-            TextPoint { row: 8, col: 8 },
-            2,
+    fn test_step_with_directions() {
+        let corner = TextPoint { row: 8, col: 8 };
+        let p = TextPoint { row: 3, col: 2 };
+
+        assert_eq!(
+            p.step(Cardinal::N, corner),
+            Some(TextPoint { row: 2, col: 2 })
+        );
+        assert_eq!(
+            p.step(Cardinal::E, corner),
+            Some(TextPoint { row: 3, col: 3 })
+        );
+        assert_eq!(
+            p.step(Cardinal::S, corner),
+            Some(TextPoint { row: 4, col: 2 })
         );
         assert_eq!(
-            res[0],
-            (
-                Direction::N,
-                vec![TextPoint { row: 3, col: 2 }, TextPoint { row: 2, col: 2 }]
-            )
+            p.step(Cardinal::W, corner),
+            Some(TextPoint { row: 3, col: 1 })
         );
+
+        // Walking north off the top of the map
+        let steps: Vec<_> =
+            std::iter::successors(Some(p), |q| q.step(Cardinal::N, corner)).collect();
+        assert_eq!(steps.len(), 4);
+        assert_eq!(steps[3], TextPoint { row: 0, col: 2 });
+
+        assert_eq!(corner.step(Cardinal::E, corner), None);
+        assert_eq!(corner.step(Cardinal::S, corner), None);
+    }
+
+    #[test]
+    fn test_parse_guards() {
+        let err = parse("..\n..").unwrap_err();
+        assert!(err.to_string().contains("there is no guard"));
+
+        for map in ["^.\n.^", "^.\n.>"] {
+            let err = parse(map).unwrap_err();
+            assert!(err.to_string().contains("line 2"));
+            assert!(err.to_string().contains("more than one guard"));
+        }
     }
 
     #[test]
     fn test_parse() {
-        let res = parse(TEST_DATA);
+        let res = parse(TEST_DATA).unwrap();
 
-        assert_eq!(
-            res.guard,
-            (Direction::N, Some(TextPoint { row: 6, col: 4 }))
-        );
+        assert_eq!(res.guard, (Cardinal::N, Some(TextPoint { row: 6, col: 4 })));
 
-        assert_eq!(res.guard_map[6][4], b'.');
+        assert_eq!(res.guard_map[TextPoint { row: 6, col: 4 }], Terrain::Open);
     }
 
     #[test]
     fn test_tick() {
-        let mut guard_map = parse(TEST_DATA);
+        let mut guard_map = parse(TEST_DATA).unwrap();
 
         guard_map.tick();
 
         assert_eq!(
             guard_map.guard,
-            (Direction::N, Some(TextPoint { row: 5, col: 4 }))
+            (Cardinal::N, Some(TextPoint { row: 5, col: 4 }))
         );
 
         guard_map.tick();
@@ -173,10 +255,17 @@ mod tests {
 
         assert_eq!(
             guard_map.guard,
-            (Direction::E, Some(TextPoint { row: 1, col: 5 }))
+            (Cardinal::E, Some(TextPoint { row: 1, col: 5 }))
         );
     }
 
+    #[test]
+    fn test_example_day6() {
+        let day6 = Day6::with_data(TEST_DATA).unwrap();
+        assert_eq!(day6.prob1_inner(), 41);
+        assert_eq!(day6.prob2_inner(), 6);
+    }
+
     //#[test]
     //fn actual() {
     //    let data = fs::read_to_string("data/day6.txt").unwrap();
@@ -203,7 +292,7 @@ mod tests {
     //            continue;
     //        }
     //        println!("Considering point {:?}", o);
-    //        let mut visited: HashSet<(Direction, TextPoint)> = HashSet::new();
+    //        let mut visited: HashSet<(Cardinal, TextPoint)> = HashSet::new();
     //        let mut guard_map = GuardMap {
     //            guard_map: replace_char_in_puzzle(&guard_map_2.guard_map, *o, "O"),
     //            guard: guard_map_2.guard,
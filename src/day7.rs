@@ -1,7 +1,15 @@
-use std::iter;
+use std::{collections::HashMap, iter};
 
 use enum_iterator::Sequence;
 use itertools::Itertools;
+use linkme::distributed_slice;
+
+use crate::{
+    answer::Answer,
+    error::{parse_at, Error, Result},
+    examples::{Example, EXAMPLES},
+    problem::{factory, Problem, ProblemFactory, PROBLEMS},
+};
 
 fn product_with_repeat<T: Clone>(
     it: impl Iterator<Item = T> + Clone,
@@ -13,20 +21,19 @@ fn product_with_repeat<T: Clone>(
         .multi_cartesian_product()
 }
 
-fn parse(data: &str) -> Vec<(i64, Vec<i64>)> {
+fn parse(data: &str) -> Result<Vec<(i64, Vec<i64>)>> {
     data.lines()
         .map(|line| {
-            let mut split = line.split(": ");
-            let res = str::parse(split.next().unwrap()).unwrap();
-            let rest = split.next().unwrap();
+            let Some((res, rest)) = line.split_once(": ") else {
+                return Err(Error::parse(data, line, "expected `result: operands`"));
+            };
 
-            (
-                res,
+            Ok((
+                parse_at(data, res)?,
                 rest.split(" ")
-                    .into_iter()
-                    .map(|s| str::parse(s).unwrap())
-                    .collect(),
-            )
+                    .map(|s| parse_at(data, s))
+                    .collect::<Result<_>>()?,
+            ))
         })
         .collect()
 }
@@ -77,13 +84,64 @@ impl BinOp for ExtendedOp {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use std::{collections::HashSet, convert::identity, fs};
+// Sum of the results that some choice of operators can produce
+fn total_calibration<T: BinOp + Clone + Sequence>(equations: &[(i64, Vec<i64>)]) -> i64 {
+    equations
+        .iter()
+        .filter_map(|(expected, operands)| {
+            let mut stack = operands.clone();
+            stack.reverse();
+
+            let mut ops = product_with_repeat(enum_iterator::all::<T>(), stack.len() - 1);
+            if ops.any(|op| eval(op, stack.clone()) == *expected) {
+                Some(expected)
+            } else {
+                None
+            }
+        })
+        .sum()
+}
 
-    use super::*;
+pub struct Day7 {
+    equations: Vec<(i64, Vec<i64>)>,
+}
+
+impl Day7 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        Ok(Day7 {
+            equations: parse(data)?,
+        })
+    }
+
+    fn prob1_inner(&self) -> i64 {
+        total_calibration::<Op>(&self.equations)
+    }
+
+    fn prob2_inner(&self) -> i64 {
+        total_calibration::<ExtendedOp>(&self.equations)
+    }
+}
+
+impl Problem for Day7 {
+    fn parse(data: &str) -> Result<Self> {
+        Day7::with_data(data)
+    }
 
-    const TEST_DATA: &str = "190: 10 19
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
+    }
+
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner().into())
+    }
+}
+
+#[distributed_slice(PROBLEMS)]
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day7".to_owned(), factory::<Day7>);
+}
+
+const TEST_DATA: &str = "190: 10 19
 3267: 81 40 27
 83: 17 5
 156: 15 6
@@ -93,6 +151,21 @@ mod tests {
 21037: 9 7 18 13
 292: 11 6 16 20";
 
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day7",
+    input: TEST_DATA,
+    part1: Some("3749"),
+    part2: Some("11387"),
+    factory: None,
+};
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, convert::identity, fs};
+
+    use super::*;
+
     #[test]
     fn test_product_with_repeat() {
         let res: Vec<Vec<i64>> = product_with_repeat(0..=1, 3).collect();
@@ -113,15 +186,22 @@ mod tests {
 
     #[test]
     fn test_parse() {
-        let res = parse(TEST_DATA);
+        let res = parse(TEST_DATA).unwrap();
         assert_eq!(res[0], (190, vec![10, 19]));
     }
 
+    #[test]
+    fn test_example_day7() {
+        let day7 = Day7::with_data(TEST_DATA).unwrap();
+        assert_eq!(day7.prob1_inner(), 3749);
+        assert_eq!(day7.prob2_inner(), 11387);
+    }
+
     #[test]
     fn test_prob1() {
         let data = fs::read_to_string("data/day7.txt").unwrap();
         //let data = TEST_DATA;
-        let data = parse(&data);
+        let data = parse(&data).unwrap();
 
         assert_eq!(
             //3749 as i64,
@@ -1,63 +1,125 @@
-use crate::day4::TextPoint;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::identity,
+};
+
+use crate::{
+    answer::Answer,
+    coord::{Coord, Vector},
+    error::Result,
+    examples::{Example, EXAMPLES},
+    grid::Grid,
+    point::TextPoint,
+    problem::{factory, Problem, ProblemFactory, PROBLEMS},
+};
 use itertools::Itertools;
+use linkme::distributed_slice;
 
-#[derive(Debug, Eq, PartialEq)]
-pub struct TextDifference {
-    row: isize,
-    col: isize,
+fn point_distance(a: TextPoint, b: TextPoint) -> Vector {
+    Coord::from(b) - Coord::from(a)
 }
 
-impl TextDifference {
-    fn apply(&self, other: TextPoint) -> Option<TextPoint> {
-        let row_diff = other.row.checked_add_signed(self.row);
-        let col_diff = other.col.checked_add_signed(self.col);
+fn find_antennas(board: &Grid<u8>) -> HashMap<u8, Vec<TextPoint>> {
+    let mut coordinates: HashMap<u8, Vec<TextPoint>> = HashMap::new();
 
-        if let (Some(row_diff), Some(col_diff)) = (row_diff, col_diff) {
-            Some(TextPoint {
-                row: row_diff,
-                col: col_diff,
-            })
-        } else {
-            None
+    for (p, c) in board.cells() {
+        if *c == b'.' {
+            continue;
         }
+        coordinates.entry(*c).or_default().push(p);
     }
 
-    fn apply_with_corner(&self, other: TextPoint, corner: TextPoint) -> Option<TextPoint> {
-        let new_point = self.apply(other);
-        match new_point {
-            Some(TextPoint { row, col }) if row <= corner.row && col <= corner.col => new_point,
-            _ => None,
-        }
+    coordinates
+}
+
+fn antinodes(coordinates: &HashMap<u8, Vec<TextPoint>>, corner: TextPoint) -> HashSet<TextPoint> {
+    coordinates
+        .values()
+        .flat_map(|v| {
+            v.iter()
+                .combinations(2)
+                .flat_map(|lr| {
+                    let (l, r) = (lr[0], lr[1]);
+                    let diff = point_distance(*l, *r);
+                    vec![l.offset(-diff, corner), r.offset(diff, corner)]
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter_map(identity)
+        .collect()
+}
+
+// Every point in line with a pair of antennas, out to the edge of the map
+fn resonant_antinodes(
+    coordinates: &HashMap<u8, Vec<TextPoint>>,
+    corner: TextPoint,
+) -> HashSet<TextPoint> {
+    coordinates
+        .values()
+        .flat_map(|v| {
+            let mut results = Vec::default();
+            for lr in v.iter().combinations(2) {
+                let (l, r) = (lr[0], lr[1]);
+                let diff = point_distance(*l, *r);
+                let mut cur_point = Some(*r);
+                while let Some(point) = cur_point {
+                    results.push(point);
+                    cur_point = point.offset(diff, corner);
+                }
+                cur_point = Some(*l);
+                while let Some(point) = cur_point {
+                    results.push(point);
+                    cur_point = point.offset(-diff, corner);
+                }
+            }
+            results
+        })
+        .collect()
+}
+
+pub struct Day8 {
+    coordinates: HashMap<u8, Vec<TextPoint>>,
+    corner: TextPoint,
+}
+
+impl Day8 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        let board = Grid::parse(data)?;
+        Ok(Day8 {
+            coordinates: find_antennas(&board),
+            corner: board.corner(),
+        })
     }
 
-    fn flip(&self) -> TextDifference {
-        TextDifference {
-            row: -self.row,
-            col: -self.col,
-        }
+    fn prob1_inner(&self) -> usize {
+        antinodes(&self.coordinates, self.corner).len()
     }
-}
 
-fn point_distance(a: TextPoint, b: TextPoint) -> TextDifference {
-    TextDifference {
-        row: (b.row as isize) - (a.row as isize),
-        col: (b.col as isize) - (a.col as isize),
+    fn prob2_inner(&self) -> usize {
+        resonant_antinodes(&self.coordinates, self.corner).len()
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use std::{
-        collections::{HashMap, HashSet},
-        convert::identity,
-        fs,
-    };
+impl Problem for Day8 {
+    fn parse(data: &str) -> Result<Self> {
+        Day8::with_data(data)
+    }
 
-    use crate::day4::char_matrix;
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
+    }
 
-    use super::*;
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner().into())
+    }
+}
 
-    const TEST_DATA: &str = "............
+#[distributed_slice(PROBLEMS)]
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day8".to_owned(), factory::<Day8>);
+}
+
+const TEST_DATA: &str = "............
 ........0...
 .....0......
 .......0....
@@ -70,41 +132,72 @@ mod tests {
 ............
 ............";
 
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day8",
+    input: TEST_DATA,
+    part1: Some("14"),
+    part2: Some("34"),
+    factory: None,
+};
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        convert::identity,
+        fs,
+    };
+
+    use crate::point::char_matrix;
+
+    use super::*;
+
     #[test]
     fn test_point_distance() {
+        let corner = TextPoint { row: 9, col: 9 };
+
         let res = point_distance(TextPoint { row: 0, col: 0 }, TextPoint { row: 1, col: 1 });
-        assert_eq!(res, TextDifference { row: 1, col: 1 });
+        assert_eq!(res, Vector::new(1, 1));
 
-        let res = res.apply(TextPoint { row: 0, col: 0 });
+        let res = TextPoint { row: 0, col: 0 }.offset(res, corner);
         assert_eq!(res, Some(TextPoint { row: 1, col: 1 }));
 
         let res = point_distance(TextPoint { row: 0, col: 1 }, TextPoint { row: 1, col: 0 });
-        assert_eq!(res, TextDifference { row: 1, col: -1 });
+        assert_eq!(res, Vector::new(1, -1));
 
-        let res1 = res.apply(TextPoint { row: 0, col: 1 });
+        let res1 = TextPoint { row: 0, col: 1 }.offset(res, corner);
         assert_eq!(res1, Some(TextPoint { row: 1, col: 0 }));
 
-        let res = res.apply(TextPoint { row: 0, col: 0 });
+        let res = TextPoint { row: 0, col: 0 }.offset(res, corner);
         assert_eq!(res, None);
 
         let res = point_distance(TextPoint { row: 0, col: 1 }, TextPoint { row: 2, col: 2 });
-        let res = res.apply_with_corner(TextPoint { row: 2, col: 2 }, TextPoint { row: 3, col: 3 });
+        let res = TextPoint { row: 2, col: 2 }.offset(res, TextPoint { row: 3, col: 3 });
         assert_eq!(res, None);
     }
 
     #[test]
     fn test_flip() {
+        let corner = TextPoint { row: 9, col: 9 };
         let res = point_distance(TextPoint { row: 0, col: 1 }, TextPoint { row: 1, col: 0 });
         assert_eq!(
-            res.apply(TextPoint { row: 0, col: 1 }),
+            TextPoint { row: 0, col: 1 }.offset(res, corner),
             Some(TextPoint { row: 1, col: 0 })
         );
         assert_eq!(
-            res.flip().apply(TextPoint { row: 1, col: 0 }),
+            TextPoint { row: 1, col: 0 }.offset(-res, corner),
             Some(TextPoint { row: 0, col: 1 })
         );
     }
 
+    #[test]
+    fn test_example_day8() {
+        let day8 = Day8::with_data(TEST_DATA).unwrap();
+        assert_eq!(day8.prob1_inner(), 14);
+        assert_eq!(day8.prob2_inner(), 34);
+    }
+
     #[test]
     fn test_parse_sample() {
         //let board = char_matrix(TEST_DATA);
@@ -152,10 +245,7 @@ mod tests {
                     .flat_map(|lr| {
                         let (l, r) = (lr[0], lr[1]);
                         let diff = point_distance(*l, *r);
-                        vec![
-                            diff.flip().apply_with_corner(*l, corner),
-                            diff.apply_with_corner(*r, corner),
-                        ]
+                        vec![l.offset(-diff, corner), r.offset(diff, corner)]
                     })
                     .collect::<Vec<_>>()
             })
@@ -175,12 +265,12 @@ mod tests {
                     let mut cur_point = Some(*r);
                     while let Some(point) = cur_point {
                         results.push(point);
-                        cur_point = diff.apply_with_corner(point, corner);
+                        cur_point = point.offset(diff, corner);
                     }
                     cur_point = Some(*l);
                     while let Some(point) = cur_point {
                         results.push(point);
-                        cur_point = diff.flip().apply_with_corner(point, corner);
+                        cur_point = point.offset(-diff, corner);
                     }
                 }
                 results
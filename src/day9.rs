@@ -1,3 +1,14 @@
+use std::collections::HashMap;
+
+use linkme::distributed_slice;
+
+use crate::{
+    answer::Answer,
+    error::{Error, Result},
+    examples::{Example, EXAMPLES},
+    problem::{factory, Problem, ProblemFactory, PROBLEMS},
+};
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 struct FileId(usize);
 
@@ -9,7 +20,7 @@ struct File {
     blocks: Vec<BlockIndex>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 struct Disk {
     blocks: Vec<(BlockIndex, FileId)>,
     free: Vec<(BlockIndex, usize)>,
@@ -102,38 +113,99 @@ fn smart_defrag(disk: &mut Disk) {
     }
 }
 
-fn parse(data: &str) -> Disk {
+// Alternating file and free space sizes, one digit each, starting with a file
+fn parse(data: &str) -> Result<Disk> {
+    let data = data.trim_end();
+    let sizes = data
+        .char_indices()
+        .map(|(i, c)| {
+            c.to_digit(10)
+                .map(|d| d as usize)
+                .ok_or_else(|| Error::parse(data, &data[i..i + c.len_utf8()], "expected a digit"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
     let mut cur_index = 0;
-    let mut cur_id = 0;
     let mut disk = Disk::default();
 
-    for s in data.as_bytes().chunks(2) {
-        let filesize = s[0] - b'0';
-        for _ in 0..filesize {
+    for (cur_id, s) in sizes.chunks(2).enumerate() {
+        for _ in 0..s[0] {
             disk.blocks.push((BlockIndex(cur_index), FileId(cur_id)));
             cur_index += 1;
         }
-        cur_id += 1;
 
-        if s.len() > 1 && s[1] >= b'0' {
-            let freespace = s[1] - b'0';
-            disk.free.push((BlockIndex(cur_index), freespace as usize));
-
-            cur_index += freespace as usize;
+        if let Some(&freespace) = s.get(1) {
+            disk.free.push((BlockIndex(cur_index), freespace));
+            cur_index += freespace;
         }
     }
 
-    disk
+    if disk.blocks.is_empty() {
+        return Err(Error::parse(data, data, "the disk has no files on it"));
+    }
+    Ok(disk)
+}
+
+pub struct Day9 {
+    disk: Disk,
+}
+
+impl Day9 {
+    pub fn with_data(data: &str) -> Result<Self> {
+        Ok(Day9 { disk: parse(data)? })
+    }
+
+    fn prob1_inner(&self) -> usize {
+        let mut disk = self.disk.clone();
+        defrag(&mut disk);
+        checksum(&disk)
+    }
+
+    fn prob2_inner(&self) -> usize {
+        let mut disk = self.disk.clone();
+        smart_defrag(&mut disk);
+        checksum(&disk)
+    }
+}
+
+impl Problem for Day9 {
+    fn parse(data: &str) -> Result<Self> {
+        Day9::with_data(data)
+    }
+
+    fn prob1(&self) -> Result<Answer> {
+        Ok(self.prob1_inner().into())
+    }
+
+    fn prob2(&self) -> Result<Answer> {
+        Ok(self.prob2_inner().into())
+    }
+}
+
+#[distributed_slice(PROBLEMS)]
+fn register_day(p: &mut HashMap<String, ProblemFactory>) {
+    p.insert("day9".to_owned(), factory::<Day9>);
 }
 
+const TEST_DATA: &str = "2333133121414131402";
+
+#[distributed_slice(EXAMPLES)]
+static EXAMPLE: Example = Example {
+    day: "day9",
+    input: TEST_DATA,
+    part1: Some("1928"),
+    part2: Some("2858"),
+    factory: None,
+};
+
 #[cfg(test)]
 mod tests {
     use std::{collections::HashSet, convert::identity, fs};
 
     use super::*;
+    use crate::error::ErrorKind;
 
     const SIMPLE_DATA: &str = "12345";
-    const TEST_DATA: &str = "2333133121414131402";
 
     fn str_to_disk(s: &str) -> Vec<(BlockIndex, FileId)> {
         s.as_bytes()
@@ -171,7 +243,7 @@ mod tests {
 
     #[test]
     fn test_simple_parse() {
-        let disk = parse(TEST_DATA);
+        let disk = parse(TEST_DATA).unwrap();
 
         assert_eq!(
             disk.blocks,
@@ -196,7 +268,7 @@ mod tests {
 
     #[test]
     fn test_sample_defrag() {
-        let mut disk = parse(TEST_DATA);
+        let mut disk = parse(TEST_DATA).unwrap();
         defrag(&mut disk);
 
         let mut sorted = disk.blocks.clone();
@@ -214,7 +286,7 @@ mod tests {
     fn test_actual_problem() {
         let data = fs::read_to_string("data/day9.txt").unwrap();
 
-        let mut disk = parse(&data);
+        let mut disk = parse(&data).unwrap();
         defrag(&mut disk);
 
         assert_eq!(checksum(&disk), 6299243228569);
@@ -222,7 +294,7 @@ mod tests {
 
     #[test]
     fn test_smart_defrag() {
-        let mut disk = parse(TEST_DATA);
+        let mut disk = parse(TEST_DATA).unwrap();
 
         smart_defrag(&mut disk);
 
@@ -232,9 +304,24 @@ mod tests {
     #[test]
     fn test_smart_defrag_problem() {
         let data = fs::read_to_string("data/day9.txt").unwrap();
-        let mut disk = parse(&data);
+        let mut disk = parse(&data).unwrap();
 
         smart_defrag(&mut disk);
         assert_eq!(checksum(&disk), 6326952672104);
     }
+
+    #[test]
+    fn test_parse_errors() {
+        let e = parse("12a4").unwrap_err();
+        assert!(matches!(
+            e.kind,
+            ErrorKind::Parse {
+                line: 1,
+                column: 3,
+                ..
+            }
+        ));
+        assert!(parse("").is_err());
+        assert!(parse("0\n").is_err());
+    }
 }
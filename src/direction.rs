@@ -0,0 +1,212 @@
+use enum_iterator::Sequence;
+
+use crate::coord::Vector;
+
+// The four ways you can walk on a grid without cutting corners
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Sequence)]
+pub enum Cardinal {
+    N,
+    E,
+    S,
+    W,
+}
+
+// All eight points of the compass, for when diagonals count too
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Sequence)]
+pub enum Compass {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Cardinal {
+    // Clockwise from north, the same order as the variants
+    pub const ALL: [Cardinal; 4] = [Cardinal::N, Cardinal::E, Cardinal::S, Cardinal::W];
+
+    fn turn(self, quarters: usize) -> Self {
+        Cardinal::ALL[(self as usize + quarters) % 4]
+    }
+
+    pub fn rotate_right(self) -> Self {
+        self.turn(1)
+    }
+
+    pub fn rotate_left(self) -> Self {
+        self.turn(3)
+    }
+
+    pub fn opposite(self) -> Self {
+        self.turn(2)
+    }
+
+    pub fn delta(self) -> Vector {
+        Compass::from(self).delta()
+    }
+
+    // In degrees, the short way round, so never more than 180
+    pub fn angle_to(self, other: Cardinal) -> u32 {
+        Compass::from(self).angle_to(other.into())
+    }
+
+    // Arrows, compass letters or up/down/left/right
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '^' | 'N' | 'U' => Some(Cardinal::N),
+            '>' | 'E' | 'R' => Some(Cardinal::E),
+            'v' | 'S' | 'D' => Some(Cardinal::S),
+            '<' | 'W' | 'L' => Some(Cardinal::W),
+            _ => None,
+        }
+    }
+}
+
+impl Compass {
+    // Clockwise from north, the same order as the variants
+    pub const ALL: [Compass; 8] = [
+        Compass::N,
+        Compass::NE,
+        Compass::E,
+        Compass::SE,
+        Compass::S,
+        Compass::SW,
+        Compass::W,
+        Compass::NW,
+    ];
+
+    fn turn(self, eighths: usize) -> Self {
+        Compass::ALL[(self as usize + eighths) % 8]
+    }
+
+    pub fn rotate_right_45(self) -> Self {
+        self.turn(1)
+    }
+
+    pub fn rotate_left_45(self) -> Self {
+        self.turn(7)
+    }
+
+    pub fn rotate_right(self) -> Self {
+        self.turn(2)
+    }
+
+    pub fn rotate_left(self) -> Self {
+        self.turn(6)
+    }
+
+    pub fn opposite(self) -> Self {
+        self.turn(4)
+    }
+
+    // One step this way, as (rows, cols) with north up the page
+    pub fn delta(self) -> Vector {
+        match self {
+            Compass::N => Vector::new(-1, 0),
+            Compass::NE => Vector::new(-1, 1),
+            Compass::E => Vector::new(0, 1),
+            Compass::SE => Vector::new(1, 1),
+            Compass::S => Vector::new(1, 0),
+            Compass::SW => Vector::new(1, -1),
+            Compass::W => Vector::new(0, -1),
+            Compass::NW => Vector::new(-1, -1),
+        }
+    }
+
+    // In degrees, the short way round, so never more than 180
+    pub fn angle_to(self, other: Compass) -> u32 {
+        let eighths = (other as u32 + 8 - self as u32) % 8;
+        45 * eighths.min(8 - eighths)
+    }
+}
+
+impl From<Cardinal> for Compass {
+    fn from(d: Cardinal) -> Self {
+        Compass::ALL[d as usize * 2]
+    }
+}
+
+// Only works for N, E, S and W
+impl TryFrom<Compass> for Cardinal {
+    type Error = Compass;
+
+    fn try_from(d: Compass) -> Result<Self, Compass> {
+        if d as usize % 2 == 0 {
+            Ok(Cardinal::ALL[d as usize / 2])
+        } else {
+            Err(d)
+        }
+    }
+}
+
+impl From<Cardinal> for Vector {
+    fn from(d: Cardinal) -> Self {
+        d.delta()
+    }
+}
+
+impl From<Compass> for Vector {
+    fn from(d: Compass) -> Self {
+        d.delta()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use enum_iterator::all;
+
+    use super::*;
+
+    #[test]
+    fn test_turns() {
+        assert_eq!(Cardinal::N.rotate_right(), Cardinal::E);
+        assert_eq!(Cardinal::N.rotate_left(), Cardinal::W);
+        assert_eq!(Cardinal::E.opposite(), Cardinal::W);
+
+        assert_eq!(Compass::N.rotate_right_45(), Compass::NE);
+        assert_eq!(Compass::N.rotate_left_45(), Compass::NW);
+        assert_eq!(Compass::SW.rotate_right(), Compass::NW);
+        assert_eq!(Compass::SE.opposite(), Compass::NW);
+
+        for d in all::<Compass>() {
+            assert_eq!(d.opposite().delta(), -d.delta());
+            assert_eq!(d.rotate_left().rotate_right(), d);
+        }
+    }
+
+    #[test]
+    fn test_angles() {
+        assert_eq!(Cardinal::N.angle_to(Cardinal::N), 0);
+        assert_eq!(Cardinal::N.angle_to(Cardinal::W), 90);
+        assert_eq!(Cardinal::W.angle_to(Cardinal::N), 90);
+        assert_eq!(Cardinal::S.angle_to(Cardinal::N), 180);
+        assert_eq!(Compass::NE.angle_to(Compass::W), 135);
+    }
+
+    #[test]
+    fn test_conversions() {
+        for d in all::<Cardinal>() {
+            assert_eq!(Cardinal::try_from(Compass::from(d)), Ok(d));
+            assert_eq!(Compass::from(d).delta(), d.delta());
+        }
+        assert_eq!(Cardinal::try_from(Compass::NE), Err(Compass::NE));
+    }
+
+    #[test]
+    fn test_from_char() {
+        for (chars, d) in [
+            ("^NU", Cardinal::N),
+            (">ER", Cardinal::E),
+            ("vSD", Cardinal::S),
+            ("<WL", Cardinal::W),
+        ] {
+            for c in chars.chars() {
+                assert_eq!(Cardinal::from_char(c), Some(d));
+            }
+        }
+        assert_eq!(Cardinal::from_char('.'), None);
+    }
+}
@@ -0,0 +1,162 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::problem::Part;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    // The puzzle input didn't look like we expected. Lines and columns count from 1.
+    Parse {
+        line: usize,
+        column: usize,
+        text: String,
+        message: String,
+    },
+    // The input parsed, but a part couldn't get an answer out of it
+    Solve(String),
+    // We never got as far as having any input
+    Input(String),
+    UnknownProblem(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    pub day: Option<String>,
+    pub part: Option<Part>,
+    pub kind: ErrorKind,
+}
+
+impl Error {
+    fn new(kind: ErrorKind) -> Self {
+        Error {
+            day: None,
+            part: None,
+            kind,
+        }
+    }
+
+    // `text` has to be a slice of `input` so we can work out where it is
+    pub fn parse(input: &str, text: &str, message: impl Display) -> Self {
+        let (line, column) = locate(input, text);
+        Error::new(ErrorKind::Parse {
+            line,
+            column,
+            text: text.to_string(),
+            message: message.to_string(),
+        })
+    }
+
+    pub fn solve(message: impl Display) -> Self {
+        Error::new(ErrorKind::Solve(message.to_string()))
+    }
+
+    pub fn input(message: impl Display) -> Self {
+        Error::new(ErrorKind::Input(message.to_string()))
+    }
+
+    pub fn unknown_problem(name: &str) -> Self {
+        Error::new(ErrorKind::UnknownProblem(name.to_string()))
+    }
+
+    // The runner knows which problem and part it was running, the problems don't
+    pub fn in_day(mut self, day: &str) -> Self {
+        self.day.get_or_insert_with(|| day.to_string());
+        self
+    }
+
+    pub fn in_part(mut self, part: Part) -> Self {
+        self.part.get_or_insert(part);
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.day, self.part) {
+            (Some(day), Some(part)) => write!(f, "{day} part {part}: ")?,
+            (Some(day), None) => write!(f, "{day}: ")?,
+            (None, Some(part)) => write!(f, "part {part}: ")?,
+            (None, None) => {}
+        }
+
+        match &self.kind {
+            ErrorKind::Parse {
+                line,
+                column,
+                text,
+                message,
+            } => write!(
+                f,
+                "could not parse input at line {line}, column {column} (`{text}`): {message}"
+            ),
+            ErrorKind::Solve(message) => write!(f, "{message}"),
+            ErrorKind::Input(message) => write!(f, "{message}"),
+            ErrorKind::UnknownProblem(name) => write!(
+                f,
+                "unknown problem `{name}` (run `list` to see what is registered)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+// Line and column of `text` inside `input`. If `text` isn't actually part of
+// `input` the best we can do is point at the start.
+fn locate(input: &str, text: &str) -> (usize, usize) {
+    let start = input.as_ptr() as usize;
+    let offset = (text.as_ptr() as usize).wrapping_sub(start);
+    if offset > input.len() || offset + text.len() > input.len() {
+        return (1, 1);
+    }
+
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+// Parse a field that was sliced out of `input`, pointing at it if it's bad
+pub fn parse_at<T>(input: &str, field: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    field.parse().map_err(|e| Error::parse(input, field, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "1 2\n3 x4\n";
+
+    #[test]
+    fn test_locate() {
+        assert_eq!(locate(INPUT, &INPUT[0..1]), (1, 1));
+        assert_eq!(locate(INPUT, &INPUT[2..3]), (1, 3));
+        assert_eq!(locate(INPUT, &INPUT[4..5]), (2, 1));
+        assert_eq!(locate(INPUT, &INPUT[6..8]), (2, 3));
+        assert_eq!(locate(INPUT, "elsewhere"), (1, 1));
+    }
+
+    #[test]
+    fn test_parse_at() {
+        assert_eq!(parse_at::<usize>(INPUT, &INPUT[2..3]), Ok(2));
+
+        let e = parse_at::<usize>(INPUT, &INPUT[6..8]).unwrap_err();
+        assert!(matches!(
+            e.kind,
+            ErrorKind::Parse {
+                line: 2,
+                column: 3,
+                ..
+            }
+        ));
+        assert_eq!(
+            e.in_day("day2").in_part(Part::One).to_string(),
+            "day2 part 1: could not parse input at line 2, column 3 (`x4`): invalid digit found in string"
+        );
+    }
+}
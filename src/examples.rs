@@ -0,0 +1,64 @@
+use linkme::distributed_slice;
+
+use crate::{
+    answer::Answer,
+    problem::{day_of, Part, ProblemFactory},
+};
+
+// A worked example from a day's puzzle text, with whichever answers the puzzle
+// gives for it. Variants are run on their day's examples too.
+pub struct Example {
+    pub day: &'static str,
+    pub input: &'static str,
+    pub part1: Option<&'static str>,
+    pub part2: Option<&'static str>,
+    // Some if the example's puzzle is set up differently to the real one, like
+    // on a smaller floor. It's used instead of the problem being run.
+    pub factory: Option<ProblemFactory>,
+}
+
+#[distributed_slice]
+pub static EXAMPLES: [Example];
+
+impl Example {
+    pub fn expected(&self, part: Part) -> Option<Answer> {
+        match part {
+            Part::One => self.part1,
+            Part::Two => self.part2,
+        }
+        .map(Answer::from)
+    }
+}
+
+// Every example for the day a problem solves
+pub fn for_problem(name: &str) -> Vec<&'static Example> {
+    EXAMPLES
+        .iter()
+        .filter(|example| example.day == day_of(name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_problem() {
+        assert!(!for_problem("day1").is_empty());
+        assert_eq!(for_problem("day3-chumsky").len(), for_problem("day3").len());
+        assert!(for_problem("day99").is_empty());
+    }
+
+    #[test]
+    fn test_expected() {
+        let example = Example {
+            day: "day1",
+            input: "",
+            part1: Some("11"),
+            part2: None,
+            factory: None,
+        };
+        assert_eq!(example.expected(Part::One), Some(Answer::from("11")));
+        assert_eq!(example.expected(Part::Two), None);
+    }
+}
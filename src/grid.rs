@@ -0,0 +1,476 @@
+use std::{
+    collections::HashMap,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    coord::{Coord, Vector},
+    error::{Error, Result},
+    point::{char_matrix, TextPoint},
+};
+
+// A rectangular grid stored row by row, indexed by `TextPoint`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    // Every row has to be as wide as the first one
+    pub fn from_rows(rows: Vec<Vec<T>>) -> std::result::Result<Self, (usize, usize, usize)> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+
+        let mut cells = Vec::with_capacity(width * height);
+        for (row, r) in rows.into_iter().enumerate() {
+            if r.len() != width {
+                return Err((row, width, r.len()));
+            }
+            cells.extend(r);
+        }
+
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(TextPoint) -> T) -> Self {
+        let cells = (0..width * height)
+            .map(|i| {
+                f(TextPoint {
+                    row: i / width,
+                    col: i % width,
+                })
+            })
+            .collect();
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // The bottom right cell, which is what the neighbour iterators clip to
+    pub fn corner(&self) -> TextPoint {
+        TextPoint {
+            row: self.height.saturating_sub(1),
+            col: self.width.saturating_sub(1),
+        }
+    }
+
+    pub fn contains(&self, p: TextPoint) -> bool {
+        p.row < self.height && p.col < self.width
+    }
+
+    pub fn get(&self, p: TextPoint) -> Option<&T> {
+        if self.contains(p) {
+            self.cells.get(p.row * self.width + p.col)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, p: TextPoint) -> Option<&mut T> {
+        if self.contains(p) {
+            self.cells.get_mut(p.row * self.width + p.col)
+        } else {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on zero, and an empty grid has no rows anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        let skip = if col < self.width {
+            col
+        } else {
+            self.cells.len()
+        };
+        self.cells.iter().skip(skip).step_by(self.width.max(1))
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = TextPoint> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| TextPoint {
+            row: i / width,
+            col: i % width,
+        })
+    }
+
+    // Every cell along with where it is, row by row
+    pub fn cells(&self) -> impl Iterator<Item = (TextPoint, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    // The same shape of grid with every cell replaced
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    // Where each cell is. Put this through the same transforms as the grid
+    // and it says where each cell of the result came from.
+    pub fn origins(&self) -> Grid<TextPoint> {
+        Grid::from_fn(self.width, self.height, |p| p)
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+}
+
+// Turning, flipping and cutting up grids. Each one builds a new grid by
+// looking up where every new cell comes from in the old one.
+impl<T: Clone> Grid<T> {
+    fn remap(&self, width: usize, height: usize, from: impl Fn(TextPoint) -> TextPoint) -> Self {
+        Grid::from_fn(width, height, |p| self[from(p)].clone())
+    }
+
+    // A quarter turn clockwise
+    pub fn rotate_right(&self) -> Self {
+        self.remap(self.height, self.width, |p| TextPoint {
+            row: self.height - 1 - p.col,
+            col: p.row,
+        })
+    }
+
+    // A quarter turn anticlockwise
+    pub fn rotate_left(&self) -> Self {
+        self.remap(self.height, self.width, |p| TextPoint {
+            row: p.col,
+            col: self.width - 1 - p.row,
+        })
+    }
+
+    // Rows become columns, mirroring it along the leading diagonal
+    pub fn transpose(&self) -> Self {
+        self.remap(self.height, self.width, |p| TextPoint {
+            row: p.col,
+            col: p.row,
+        })
+    }
+
+    // Mirrored left to right
+    pub fn flip_horizontal(&self) -> Self {
+        self.remap(self.width, self.height, |p| TextPoint {
+            row: p.row,
+            col: self.width - 1 - p.col,
+        })
+    }
+
+    // Upside down
+    pub fn flip_vertical(&self) -> Self {
+        self.remap(self.width, self.height, |p| TextPoint {
+            row: self.height - 1 - p.row,
+            col: p.col,
+        })
+    }
+
+    // The cells from the top left to the bottom right, inclusive, or None if
+    // that's not all on the grid
+    pub fn crop(&self, bounds: (TextPoint, TextPoint)) -> Option<Self> {
+        let (top_left, bottom_right) = bounds;
+        if !self.contains(bottom_right)
+            || top_left.row > bottom_right.row
+            || top_left.col > bottom_right.col
+        {
+            return None;
+        }
+
+        Some(self.remap(
+            bottom_right.col - top_left.col + 1,
+            bottom_right.row - top_left.row + 1,
+            |p| TextPoint {
+                row: top_left.row + p.row,
+                col: top_left.col + p.col,
+            },
+        ))
+    }
+
+    // Copies laid side by side, `across` wide and `down` high
+    pub fn tile(&self, across: usize, down: usize) -> Self {
+        self.remap(self.width * across, self.height * down, |p| TextPoint {
+            row: p.row % self.height,
+            col: p.col % self.width,
+        })
+    }
+}
+
+impl<T: PartialEq> Grid<T> {
+    // Where `value` appears, row by row
+    pub fn find(&self, value: &T) -> Vec<TextPoint> {
+        self.cells()
+            .filter(|(_, c)| *c == value)
+            .map(|(p, _)| p)
+            .collect()
+    }
+}
+
+impl Grid<u8> {
+    pub fn parse(data: &str) -> Result<Self> {
+        Grid::from_rows(char_matrix(data)).map_err(|(row, width, found)| {
+            let line = data.lines().nth(row).unwrap_or(data);
+            Error::parse(
+                data,
+                line,
+                format!("expected a row {width} wide, this one is {found}"),
+            )
+        })
+    }
+}
+
+impl<T> Index<TextPoint> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: TextPoint) -> &T {
+        self.get(p)
+            .unwrap_or_else(|| panic!("{p:?} is outside a {}x{} grid", self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<TextPoint> for Grid<T> {
+    fn index_mut(&mut self, p: TextPoint) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("{p:?} is outside a {width}x{height} grid"))
+    }
+}
+
+// Only the cells something is in, so the world can be as big as it likes. A
+// toroidal one wraps round at its edges, so every coordinate lands on it somewhere.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Coord, T>,
+    size: Option<Vector>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        SparseGrid {
+            cells: HashMap::new(),
+            size: None,
+        }
+    }
+
+    pub fn toroidal(width: i64, height: i64) -> Self {
+        SparseGrid {
+            cells: HashMap::new(),
+            size: Some(Vector::new(height, width)),
+        }
+    }
+
+    // (rows, cols) for a toroidal grid, nothing for an unbounded one
+    pub fn size(&self) -> Option<Vector> {
+        self.size
+    }
+
+    // Where `c` really is. Unbounded grids leave it alone.
+    pub fn wrap(&self, c: Coord) -> Coord {
+        match self.size {
+            Some(size) => Coord::new(c.row.rem_euclid(size.row), c.col.rem_euclid(size.col)),
+            None => c,
+        }
+    }
+
+    pub fn get(&self, c: Coord) -> Option<&T> {
+        self.cells.get(&self.wrap(c))
+    }
+
+    pub fn get_mut(&mut self, c: Coord) -> Option<&mut T> {
+        let c = self.wrap(c);
+        self.cells.get_mut(&c)
+    }
+
+    pub fn insert(&mut self, c: Coord, value: T) -> Option<T> {
+        let c = self.wrap(c);
+        self.cells.insert(c, value)
+    }
+
+    pub fn remove(&mut self, c: Coord) -> Option<T> {
+        let c = self.wrap(c);
+        self.cells.remove(&c)
+    }
+
+    pub fn entry(&mut self, c: Coord) -> std::collections::hash_map::Entry<'_, Coord, T> {
+        let c = self.wrap(c);
+        self.cells.entry(c)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // In no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.cells.iter().map(|(c, v)| (*c, v))
+    }
+
+    // The top left and bottom right of everything that's been put in
+    pub fn bounds(&self) -> Option<(Coord, Coord)> {
+        let rows = self.cells.keys().map(|c| c.row);
+        let cols = self.cells.keys().map(|c| c.col);
+        Some((
+            Coord::new(rows.clone().min()?, cols.clone().min()?),
+            Coord::new(rows.max()?, cols.max()?),
+        ))
+    }
+}
+
+impl<T> FromIterator<(Coord, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Coord, T)>>(iter: I) -> Self {
+        SparseGrid {
+            cells: iter.into_iter().collect(),
+            size: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    fn text_point(row: usize, col: usize) -> TextPoint {
+        TextPoint { row, col }
+    }
+
+    #[test]
+    fn test_parse() {
+        let g = Grid::parse("abc\ndef\n").unwrap();
+        assert_eq!((g.width(), g.height()), (3, 2));
+        assert_eq!(g.corner(), text_point(1, 2));
+        assert_eq!(g[text_point(1, 0)], b'd');
+        assert_eq!(g.get(text_point(2, 0)), None);
+        assert_eq!(g.get(text_point(0, 3)), None);
+    }
+
+    #[test]
+    fn test_parse_ragged() {
+        let err = Grid::parse("abc\nde\nfgh").unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Parse { line: 2, .. }));
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let g = Grid::parse("abc\ndef").unwrap();
+        assert_eq!(g.rows().collect::<Vec<_>>(), vec![b"abc", b"def"]);
+        assert_eq!(g.column(1).copied().collect::<Vec<_>>(), b"be");
+        assert_eq!(g.column(3).count(), 0);
+    }
+
+    #[test]
+    fn test_cells_and_find() {
+        let mut g = Grid::parse("ab\nba").unwrap();
+        assert_eq!(g.find(&b'a'), vec![text_point(0, 0), text_point(1, 1)]);
+
+        *g.get_mut(text_point(0, 1)).unwrap() = b'a';
+        assert_eq!(g.cells().filter(|(_, c)| **c == b'a').count(), 3,);
+        assert_eq!(g.cells().last(), Some((text_point(1, 1), &b'a')));
+    }
+
+    #[test]
+    fn test_transforms() {
+        let g = Grid::parse("ab\ncd\nef").unwrap();
+        let text = |g: &Grid<u8>| {
+            g.rows()
+                .map(|r| String::from_utf8(r.to_vec()).unwrap())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        assert_eq!(text(&g.rotate_right()), "eca\nfdb");
+        assert_eq!(text(&g.rotate_left()), "bdf\nace");
+        assert_eq!(text(&g.transpose()), "ace\nbdf");
+        assert_eq!(text(&g.flip_horizontal()), "ba\ndc\nfe");
+        assert_eq!(text(&g.flip_vertical()), "ef\ncd\nab");
+        assert_eq!(g.rotate_right().rotate_left(), g);
+        assert_eq!(g.transpose(), g.rotate_right().flip_horizontal());
+
+        // The origins follow the cells about
+        let turned = g.origins().rotate_right();
+        for (p, c) in g.rotate_right().cells() {
+            assert_eq!(g[turned[p]], *c);
+        }
+    }
+
+    #[test]
+    fn test_crop_and_tile() {
+        let g = Grid::parse("abc\ndef\nghi").unwrap();
+        let bounds = (TextPoint { row: 1, col: 1 }, TextPoint { row: 2, col: 2 });
+
+        assert_eq!(g.crop(bounds), Some(Grid::parse("ef\nhi").unwrap()));
+        assert_eq!(
+            g.origins().crop(bounds).unwrap()[TextPoint { row: 0, col: 0 }],
+            TextPoint { row: 1, col: 1 }
+        );
+        assert_eq!(
+            g.crop((TextPoint { row: 1, col: 1 }, TextPoint { row: 3, col: 1 })),
+            None
+        );
+
+        let tiled = Grid::parse("ab\ncd").unwrap().tile(3, 2);
+        assert_eq!(
+            tiled,
+            Grid::parse("ababab\ncdcdcd\nababab\ncdcdcd").unwrap()
+        );
+        assert_eq!(
+            Grid::parse("ab\ncd").unwrap().origins().tile(3, 2)[TextPoint { row: 3, col: 4 }],
+            TextPoint { row: 1, col: 0 }
+        );
+    }
+
+    #[test]
+    fn test_sparse() {
+        let mut g: SparseGrid<u8> = [(Coord::new(-5, 2), b'a'), (Coord::new(3, -1), b'b')]
+            .into_iter()
+            .collect();
+        assert_eq!(g.get(Coord::new(-5, 2)), Some(&b'a'));
+        assert_eq!(g.get(Coord::new(0, 0)), None);
+        assert_eq!(g.bounds(), Some((Coord::new(-5, -1), Coord::new(3, 2))));
+
+        g.insert(Coord::new(100, 100), b'c');
+        assert_eq!(g.len(), 3);
+        assert_eq!(g.remove(Coord::new(-5, 2)), Some(b'a'));
+        assert_eq!(g.bounds(), Some((Coord::new(3, -1), Coord::new(100, 100))));
+    }
+
+    #[test]
+    fn test_toroidal() {
+        let mut g = SparseGrid::toroidal(11, 7);
+        assert_eq!(g.wrap(Coord::new(7, 11)), Coord::new(0, 0));
+        assert_eq!(g.wrap(Coord::new(-1, -3)), Coord::new(6, 8));
+
+        *g.entry(Coord::new(8, 1)).or_insert(0) += 1;
+        *g.entry(Coord::new(1, 12)).or_insert(0) += 1;
+        assert_eq!(g.len(), 1);
+        assert_eq!(g.get(Coord::new(1, 1)), Some(&2));
+    }
+}
@@ -0,0 +1,91 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::problem::day_of;
+
+pub const DEFAULT_DATA_DIR: &str = "data";
+
+// Where a problem's puzzle input comes from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InputSource {
+    // Exactly this file, whatever problem is being run
+    File(PathBuf),
+    // Standard input, spelled `-` on the command line
+    Stdin,
+    // `<dir>/dayN.txt`, picked by the problem's name
+    DataDir(PathBuf),
+}
+
+impl Default for InputSource {
+    fn default() -> Self {
+        InputSource::DataDir(PathBuf::from(DEFAULT_DATA_DIR))
+    }
+}
+
+impl InputSource {
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            InputSource::Stdin
+        } else {
+            InputSource::File(PathBuf::from(arg))
+        }
+    }
+
+    pub fn describe(&self, name: &str) -> String {
+        match self {
+            InputSource::File(path) => path.display().to_string(),
+            InputSource::Stdin => "<stdin>".to_string(),
+            InputSource::DataDir(dir) => data_file(dir, name).display().to_string(),
+        }
+    }
+
+    pub fn read(&self, name: &str) -> io::Result<String> {
+        match self {
+            InputSource::File(path) => fs::read_to_string(path),
+            InputSource::Stdin => {
+                let mut data = String::new();
+                io::stdin().read_to_string(&mut data)?;
+                Ok(data)
+            }
+            InputSource::DataDir(dir) => fs::read_to_string(data_file(dir, name)),
+        }
+    }
+}
+
+// Variants like "day3-chumsky" share the input of the day they solve
+pub fn data_file(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.txt", day_of(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_file() {
+        let dir = Path::new("data");
+        assert_eq!(data_file(dir, "day1"), PathBuf::from("data/day1.txt"));
+        assert_eq!(
+            data_file(dir, "day3-chumsky"),
+            PathBuf::from("data/day3.txt")
+        );
+    }
+
+    #[test]
+    fn test_from_arg() {
+        assert_eq!(InputSource::from_arg("-"), InputSource::Stdin);
+        assert_eq!(
+            InputSource::from_arg("in.txt"),
+            InputSource::File(PathBuf::from("in.txt"))
+        );
+    }
+
+    #[test]
+    fn test_read_data_dir() {
+        let data = InputSource::default().read("day3-chumsky").unwrap();
+        assert_eq!(data, fs::read_to_string("data/day3.txt").unwrap());
+    }
+}
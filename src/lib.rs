@@ -0,0 +1,19 @@
+// The parts of the puzzles that aren't any one day's: grids and the ways to
+// walk and search them, drawing them, and what every problem looks like to
+// the runner. The days themselves, and the runner, are in the binary.
+pub mod answer;
+pub mod blocking;
+pub mod cell;
+pub mod coord;
+pub mod direction;
+pub mod error;
+pub mod grid;
+pub mod movement;
+pub mod point;
+pub mod problem;
+pub mod region;
+pub mod render;
+pub mod search;
+pub mod trace;
+pub mod walk;
+pub mod wordsearch;
@@ -1,7 +1,22 @@
-use std::{collections::HashMap, env::args};
+use std::{collections::HashMap, env::args, path::PathBuf, process::ExitCode};
 
-use problem::{Problem, PROBLEMS};
+use aoc2024::{
+    answer, blocking, cell, coord, direction, error, grid, movement, point, problem, region,
+    render, search, trace, walk, wordsearch,
+};
 
+use answer::Answer;
+
+use cli::{Command, Target, USAGE};
+use error::Error;
+use examples::Example;
+use input::InputSource;
+use itertools::Itertools;
+use problem::{Part, ProblemFactory, PROBLEMS};
+use verify::{Answers, Outcome};
+
+mod bench;
+mod cli;
 mod day1;
 mod day10;
 mod day11;
@@ -21,19 +36,369 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
-mod problem;
+mod examples;
+mod input;
+mod verify;
+
+fn read_input(name: &str, input: &InputSource) -> Result<String, Error> {
+    input.read(name).map_err(|e| {
+        Error::input(format!(
+            "could not read input from {}: {e}",
+            input.describe(name)
+        ))
+        .in_day(name)
+    })
+}
+
+// Hands each answer to `report` as soon as it's worked out
+fn solve(
+    name: &str,
+    factory: ProblemFactory,
+    data: &str,
+    parts: &[Part],
+    mut report: impl FnMut(Part, &Answer),
+) -> Result<(), Error> {
+    trace::for_problem(name, || {
+        let problem = factory(data).map_err(|e| e.in_day(name))?;
+
+        for p in parts {
+            let res = match p {
+                Part::One => problem.prob1(),
+                Part::Two => problem.prob2(),
+            };
+            report(*p, &res.map_err(|e| e.in_day(name).in_part(*p))?);
+        }
+        Ok(())
+    })
+}
+
+// Hands `f` each of a problem's examples, with the parts it has answers for.
+// Some days have none, since the examples are smaller than the real puzzle.
+fn for_each_example(
+    name: &str,
+    part: Option<Part>,
+    mut f: impl FnMut(&Example, &[Part]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let examples = examples::for_problem(name);
+    if examples.is_empty() {
+        println!("No examples registered");
+    }
+
+    for (i, example) in examples.into_iter().enumerate() {
+        let parts: Vec<_> = Part::selected(part)
+            .into_iter()
+            .filter(|p| example.expected(*p).is_some())
+            .collect();
+        if !parts.is_empty() {
+            println!("Example {}", i + 1);
+            f(example, &parts)?;
+        }
+    }
+    Ok(())
+}
+
+// Keeps going after a failure so `--all` reports everything that's broken
+fn for_each_target(
+    problems: &HashMap<String, ProblemFactory>,
+    target: Target,
+    mut f: impl FnMut(&str, ProblemFactory) -> Result<(), Error>,
+) -> Vec<Error> {
+    match target {
+        Target::Day(name) => match problems.get(&name) {
+            Some(factory) => f(&name, *factory).err().into_iter().collect(),
+            None => vec![Error::unknown_problem(&name)],
+        },
+        Target::All => problem::sorted_names(problems)
+            .into_iter()
+            .filter_map(|name| {
+                println!("{name}");
+                f(&name, problems[&name]).err()
+            })
+            .collect(),
+    }
+}
+
+// Tracing a name that doesn't exist would quietly trace nothing, so it's
+// checked like any other target. A day's name covers its variants.
+fn configure_trace(
+    problems: &HashMap<String, ProblemFactory>,
+    traced: Vec<(String, trace::Level)>,
+    format: render::Format,
+) -> Result<(), Error> {
+    if let Some((name, _)) = traced.iter().find(|(name, _)| {
+        problem::variants(problems, name).is_empty() && !problems.contains_key(name)
+    }) {
+        return Err(Error::unknown_problem(name));
+    }
+    trace::configure(traced, format);
+    Ok(())
+}
+
+fn run(
+    problems: &HashMap<String, ProblemFactory>,
+    target: Target,
+    part: Option<Part>,
+    input: &InputSource,
+    examples: bool,
+) -> Vec<Error> {
+    let print = |p: Part, res: &Answer| println!("Problem {p}: {res}");
+    for_each_target(problems, target, |name, factory| {
+        if examples {
+            for_each_example(name, part, |example, parts| {
+                solve(
+                    name,
+                    example.factory.unwrap_or(factory),
+                    example.input,
+                    parts,
+                    print,
+                )
+            })
+        } else {
+            let data = read_input(name, input)?;
+            solve(name, factory, &data, &Part::selected(part), print)
+        }
+    })
+}
+
+// Like `run`, but checks every answer, either against the answers file or the
+// ones the examples come with. A wrong answer is an error like any other so it
+// fails the exit code; one we don't know, or a part that isn't solved yet, is
+// only counted.
+fn verify(
+    problems: &HashMap<String, ProblemFactory>,
+    target: Target,
+    part: Option<Part>,
+    input: &InputSource,
+    answers: Option<PathBuf>,
+    examples: bool,
+) -> Vec<Error> {
+    let answers = if examples {
+        Answers::default()
+    } else {
+        let path = answers.unwrap_or_else(|| match input {
+            InputSource::DataDir(dir) => dir.join(verify::ANSWERS_FILE),
+            _ => PathBuf::from(input::DEFAULT_DATA_DIR).join(verify::ANSWERS_FILE),
+        });
+        match Answers::load(&path) {
+            Ok(answers) => answers,
+            Err(e) => return vec![e],
+        }
+    };
+
+    let (mut passed, mut unknown, mut unsolved) = (0, 0, 0);
+    let mut wrong = vec![];
+    let mut record = |name: &str, p: Part, res: &Answer, outcome: Outcome| {
+        println!("Problem {p}: {res} ... {outcome}");
+        match outcome {
+            Outcome::Pass => passed += 1,
+            Outcome::Unknown => unknown += 1,
+            Outcome::Unsolved => unsolved += 1,
+            Outcome::Fail { expected } => wrong.push(
+                Error::solve(format!("expected {expected}, got {res}"))
+                    .in_day(name)
+                    .in_part(p),
+            ),
+        }
+    };
+    let mut errors = for_each_target(problems, target, |name, factory| {
+        if examples {
+            for_each_example(name, part, |example, parts| {
+                solve(
+                    name,
+                    example.factory.unwrap_or(factory),
+                    example.input,
+                    parts,
+                    |p, res| {
+                        let expected = example.expected(p);
+                        record(name, p, res, verify::outcome(expected.as_ref(), res))
+                    },
+                )
+            })
+        } else {
+            let data = read_input(name, input)?;
+            solve(name, factory, &data, &Part::selected(part), |p, res| {
+                record(name, p, res, answers.check(name, p, res))
+            })
+        }
+    });
+
+    println!(
+        "{passed} passed, {} failed, {unknown} unknown, {unsolved} unsolved",
+        wrong.len()
+    );
+    errors.extend(wrong);
+    errors
+}
 
-fn main() {
-    let mut problems: HashMap<String, fn() -> Box<dyn Problem>> = HashMap::new();
+fn bench(
+    problems: &HashMap<String, ProblemFactory>,
+    target: Target,
+    part: Option<Part>,
+    input: &InputSource,
+    iterations: usize,
+    output: PathBuf,
+) -> Vec<Error> {
+    let mut rows = vec![];
+    let mut errors = for_each_target(problems, target, |name, factory| {
+        let data = read_input(name, input)?;
+        let timings =
+            bench::bench(name, factory, &data, part, iterations).map_err(|e| e.in_day(name))?;
+        for row in timings.iter() {
+            println!("{row}");
+        }
+        rows.extend(timings);
+        Ok(())
+    });
 
-    for p in PROBLEMS {
-        p(&mut problems);
+    if !rows.is_empty() {
+        match bench::save(&output, &rows) {
+            Ok(()) => println!("Saved timings to {}", output.display()),
+            Err(e) => errors.push(e),
+        }
     }
+    errors
+}
+
+// Runs every variant of a day on the same input. Variants that haven't solved
+// a part don't count, but any two answers that differ are an error.
+fn compare(
+    problems: &HashMap<String, ProblemFactory>,
+    target: Target,
+    part: Option<Part>,
+    input: &InputSource,
+) -> Vec<Error> {
+    let days: Vec<String> = match target {
+        Target::Day(name) => vec![problem::day_of(&name).to_string()],
+        Target::All => problem::sorted_names(problems)
+            .iter()
+            .map(|name| problem::day_of(name).to_string())
+            .dedup()
+            .filter(|day| problem::variants(problems, day).len() > 1)
+            .collect(),
+    };
+
+    let mut errors = vec![];
+    for day in days {
+        let names = problem::variants(problems, &day);
+        if names.is_empty() {
+            errors.push(Error::unknown_problem(&day));
+            continue;
+        }
+        println!("{day}");
+
+        let data = match read_input(&day, input) {
+            Ok(data) => data,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+
+        let mut results: Vec<(Part, String, Answer)> = vec![];
+        for name in names.iter() {
+            let res = solve(
+                name,
+                problems[name],
+                &data,
+                &Part::selected(part),
+                |p, res| results.push((p, name.clone(), res.clone())),
+            );
+            errors.extend(res.err());
+        }
 
-    let mut problem = problems.get(&args().nth(1).unwrap()).unwrap()();
-    let prob1 = problem.prob1();
-    let prob2 = problem.prob2();
+        for p in [Part::One, Part::Two] {
+            let answers: Vec<_> = results.iter().filter(|(q, _, _)| *q == p).collect();
+            if answers.is_empty() {
+                continue;
+            }
 
-    println!("Problem 1: {}", prob1);
-    println!("Problem 2: {}", prob2);
+            let agree = answer::all_agree(answers.iter().map(|(_, _, res)| res));
+            println!(
+                "Problem {p}: {}{}",
+                answers
+                    .iter()
+                    .map(|(_, name, res)| format!("{name} {res}"))
+                    .join(", "),
+                if agree { "" } else { " ... DISAGREE" }
+            );
+            if !agree {
+                errors.push(
+                    Error::solve("the variants don't agree")
+                        .in_day(&day)
+                        .in_part(p),
+                );
+            }
+        }
+    }
+    errors
+}
+
+fn main() -> ExitCode {
+    let problems = problem::registry();
+
+    let command = match cli::parse_args(args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let errors = match command {
+        Command::Help => {
+            println!("{USAGE}");
+            vec![]
+        }
+        Command::List => {
+            for name in problem::sorted_names(&problems) {
+                println!("{name}");
+            }
+            vec![]
+        }
+        Command::Run {
+            target,
+            part,
+            input,
+            examples,
+            trace,
+            trace_format,
+        } => match configure_trace(&problems, trace, trace_format) {
+            Ok(()) => run(&problems, target, part, &input, examples),
+            Err(e) => vec![e],
+        },
+        Command::Verify {
+            target,
+            part,
+            input,
+            answers,
+            examples,
+            trace,
+            trace_format,
+        } => match configure_trace(&problems, trace, trace_format) {
+            Ok(()) => verify(&problems, target, part, &input, answers, examples),
+            Err(e) => vec![e],
+        },
+        Command::Bench {
+            target,
+            part,
+            input,
+            iterations,
+            output,
+        } => bench(&problems, target, part, &input, iterations, output),
+        Command::Compare {
+            target,
+            part,
+            input,
+        } => compare(&problems, target, part, &input),
+    };
+
+    for e in errors.iter() {
+        eprintln!("error: {e}");
+    }
+
+    if errors.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
 }
@@ -0,0 +1,190 @@
+use crate::{cell::Terrain, direction::Cardinal, grid::Grid, point::TextPoint, search::Graph};
+
+// How something gets round a maze, and what each kind of move costs. Turning
+// is either a move of its own, or always comes with a step the new way. Free
+// turns are fine, but free turns on the spot go round in a loop, so there's
+// no counting the cheapest paths.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Movement {
+    pub forward: usize,
+    pub turn_left: usize,
+    pub turn_right: usize,
+    // None if it can't turn right round
+    pub reverse: Option<usize>,
+    pub turn_in_place: bool,
+    pub start_heading: Cardinal,
+    // Some if it has to be facing a particular way at the end
+    pub end_heading: Option<Cardinal>,
+}
+
+impl Movement {
+    // What it costs to face `to` when facing `from`, if it can
+    fn turn(&self, from: Cardinal, to: Cardinal) -> Option<usize> {
+        if to == from {
+            Some(0)
+        } else if to == from.rotate_left() {
+            Some(self.turn_left)
+        } else if to == from.rotate_right() {
+            Some(self.turn_right)
+        } else {
+            self.reverse
+        }
+    }
+
+    // Every move from `position` facing `heading`, with its cost, where it
+    // ends up and which way it's facing after
+    pub fn moves(
+        &self,
+        maze: &Grid<Terrain>,
+        position: TextPoint,
+        heading: Cardinal,
+    ) -> Vec<(usize, TextPoint, Cardinal)> {
+        Cardinal::ALL
+            .into_iter()
+            .filter_map(|d| {
+                let turn = self.turn(heading, d)?;
+                if d != heading && self.turn_in_place {
+                    return Some((turn, position, d));
+                }
+
+                let next = position
+                    .step(d, maze.corner())
+                    .filter(|n| maze[*n] != Terrain::Wall)?;
+                Some((turn + self.forward, next, d))
+            })
+            .collect()
+    }
+
+    pub fn arrived(&self, (position, heading): &(TextPoint, Cardinal), goal: TextPoint) -> bool {
+        *position == goal && self.end_heading.is_none_or(|h| h == *heading)
+    }
+}
+
+// Something moving round `maze` the way `movement` says, to search with
+pub struct Vehicle<'a> {
+    pub maze: &'a Grid<Terrain>,
+    pub movement: Movement,
+}
+
+impl Graph for Vehicle<'_> {
+    type State = (TextPoint, Cardinal);
+
+    fn neighbours(
+        &self,
+        (position, heading): &(TextPoint, Cardinal),
+    ) -> impl Iterator<Item = (usize, (TextPoint, Cardinal))> {
+        self.movement
+            .moves(self.maze, *position, *heading)
+            .into_iter()
+            .map(|(cost, p, d)| (cost, (p, d)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::dijkstra;
+
+    const CAR: Movement = Movement {
+        forward: 1,
+        turn_left: 10,
+        turn_right: 1,
+        reverse: None,
+        turn_in_place: false,
+        start_heading: Cardinal::E,
+        end_heading: None,
+    };
+
+    fn text_point(row: usize, col: usize) -> TextPoint {
+        TextPoint { row, col }
+    }
+
+    #[test]
+    fn test_moves() {
+        let (maze, _) = Grid::parse_cells("...\n...\n...", &[]).unwrap();
+        let centre = text_point(1, 1);
+
+        assert_eq!(
+            CAR.moves(&maze, centre, Cardinal::E),
+            vec![
+                (11, text_point(0, 1), Cardinal::N),
+                (1, text_point(1, 2), Cardinal::E),
+                (2, text_point(2, 1), Cardinal::S),
+            ]
+        );
+
+        let tank = Movement {
+            reverse: Some(5),
+            turn_in_place: true,
+            ..CAR
+        };
+        assert_eq!(
+            tank.moves(&maze, centre, Cardinal::E),
+            vec![
+                (10, centre, Cardinal::N),
+                (1, text_point(1, 2), Cardinal::E),
+                (1, centre, Cardinal::S),
+                (5, centre, Cardinal::W),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        let (maze, _) = Grid::parse_cells("..\n..", &[]).unwrap();
+        let goal = text_point(1, 1);
+        let start = (text_point(0, 0), CAR.start_heading);
+
+        // Along and then a cheap right turn
+        let car = Vehicle {
+            maze: &maze,
+            movement: CAR,
+        };
+        let search = dijkstra(&car, start, |s| car.movement.arrived(s, goal));
+        assert_eq!(search.cost(), Some(3));
+
+        // Ending up facing east takes a left turn somewhere
+        let car = Vehicle {
+            maze: &maze,
+            movement: Movement {
+                end_heading: Some(Cardinal::E),
+                ..CAR
+            },
+        };
+        let search = dijkstra(&car, start, |s| car.movement.arrived(s, goal));
+        assert_eq!(search.cost(), Some(13));
+    }
+
+    #[test]
+    fn test_free_turns() {
+        let (maze, _) = Grid::parse_cells("..\n..", &[]).unwrap();
+        let goal = text_point(1, 1);
+        let start = (text_point(0, 0), CAR.start_heading);
+        let free = Movement {
+            turn_left: 0,
+            turn_right: 0,
+            ..CAR
+        };
+
+        // Right then down, or down then right
+        let car = Vehicle {
+            maze: &maze,
+            movement: free,
+        };
+        let search = dijkstra(&car, start, |s| car.movement.arrived(s, goal));
+        assert_eq!(search.cost(), Some(2));
+        assert_eq!(search.count_paths(), Some(2));
+
+        // Spinning on the spot is free too, as often as you like
+        let tank = Vehicle {
+            maze: &maze,
+            movement: Movement {
+                turn_in_place: true,
+                ..free
+            },
+        };
+        let search = dijkstra(&tank, start, |s| tank.movement.arrived(s, goal));
+        assert_eq!(search.cost(), Some(2));
+        assert_eq!(search.count_paths(), None);
+    }
+}
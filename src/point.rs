@@ -0,0 +1,98 @@
+use crate::{
+    coord::{Coord, Vector},
+    direction::{Cardinal, Compass},
+};
+
+// Intuitively we use row/col because that's how we index Vec<String>
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Hash)]
+pub struct TextPoint {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl TextPoint {
+    // None if we'd fall off the top or left, or go past `corner`
+    pub fn offset(&self, v: Vector, corner: TextPoint) -> Option<TextPoint> {
+        (Coord::from(*self) + v).within(corner)
+    }
+
+    pub fn step(&self, direction: impl Into<Vector>, corner: TextPoint) -> Option<TextPoint> {
+        self.offset(direction.into(), corner)
+    }
+
+    // These don't allocate, and skip anything off the grid
+    pub fn neighbours4(self, corner: TextPoint) -> impl Iterator<Item = (Cardinal, TextPoint)> {
+        self.neighbours_in(&Cardinal::ALL, corner)
+    }
+
+    pub fn neighbours8(self, corner: TextPoint) -> impl Iterator<Item = (Compass, TextPoint)> {
+        self.neighbours_in(&Compass::ALL, corner)
+    }
+
+    pub fn neighbours_in<D: Copy + Into<Vector>>(
+        self,
+        directions: &[D],
+        corner: TextPoint,
+    ) -> impl Iterator<Item = (D, TextPoint)> + '_ {
+        directions
+            .iter()
+            .filter_map(move |d| Some((*d, self.step(*d, corner)?)))
+    }
+
+    // Any shape of neighbourhood, as offsets from here
+    pub fn stencil(
+        self,
+        offsets: &[Vector],
+        corner: TextPoint,
+    ) -> impl Iterator<Item = (Vector, TextPoint)> + '_ {
+        offsets
+            .iter()
+            .filter_map(move |o| Some((*o, self.offset(*o, corner)?)))
+    }
+}
+
+pub fn char_matrix(data: &str) -> Vec<Vec<u8>> {
+    data.lines().map(|s| s.as_bytes().to_vec()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbours() {
+        let corner = TextPoint { row: 3, col: 3 };
+        let origin = TextPoint { row: 0, col: 0 };
+
+        assert_eq!(
+            origin.neighbours4(corner).collect::<Vec<_>>(),
+            vec![
+                (Cardinal::E, TextPoint { row: 0, col: 1 }),
+                (Cardinal::S, TextPoint { row: 1, col: 0 })
+            ]
+        );
+        assert_eq!(origin.neighbours8(corner).count(), 3);
+        assert_eq!(TextPoint { row: 1, col: 1 }.neighbours8(corner).count(), 8);
+        assert_eq!(corner.neighbours4(corner).count(), 2);
+
+        // A knight's moves
+        let knight = [
+            (-2, 1),
+            (-1, 2),
+            (1, 2),
+            (2, 1),
+            (2, -1),
+            (1, -2),
+            (-1, -2),
+            (-2, -1),
+        ]
+        .map(|(row, col)| Vector::new(row, col));
+        assert_eq!(
+            origin.stencil(&knight, corner).collect::<Vec<_>>(),
+            vec![
+                (Vector::new(1, 2), TextPoint { row: 1, col: 2 }),
+                (Vector::new(2, 1), TextPoint { row: 2, col: 1 })
+            ]
+        );
+    }
+}
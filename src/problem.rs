@@ -2,10 +2,137 @@ use std::{collections::HashMap, fmt::Display};
 
 use linkme::distributed_slice;
 
+use crate::{answer::Answer, error::Result};
+
+// Each problem is built from the text of its puzzle input, which might not parse
+pub type ProblemFactory = fn(&str) -> Result<Box<dyn Problem>>;
+
 #[distributed_slice]
-pub static PROBLEMS: [fn(&mut HashMap<String, fn() -> Box<dyn Problem>>)];
+pub static PROBLEMS: [fn(&mut HashMap<String, ProblemFactory>)];
 
+// A problem is its parsed puzzle input. Both parts only get to look at it, so
+// either can run on its own and the parsing is done once for the pair.
 pub trait Problem {
-    fn prob1(&mut self) -> Box<dyn Display>;
-    fn prob2(&mut self) -> Box<dyn Display>;
+    fn parse(data: &str) -> Result<Self>
+    where
+        Self: Sized;
+    fn prob1(&self) -> Result<Answer>;
+    fn prob2(&self) -> Result<Answer>;
+}
+
+// The `ProblemFactory` for a problem, for registering it
+pub fn factory<P: Problem + 'static>(data: &str) -> Result<Box<dyn Problem>> {
+    Ok(Box::new(P::parse(data)?))
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    // Both parts, unless only one was asked for
+    pub fn selected(only: Option<Part>) -> Vec<Part> {
+        [Part::One, Part::Two]
+            .into_iter()
+            .filter(|p| only.is_none_or(|only| only == *p))
+            .collect()
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+// Everything that registered itself through `PROBLEMS`
+pub fn registry() -> HashMap<String, ProblemFactory> {
+    let mut problems = HashMap::new();
+    for p in PROBLEMS {
+        p(&mut problems);
+    }
+    problems
+}
+
+// Names in day order, so "day10" comes after "day9" rather than "day1"
+pub fn sorted_names(problems: &HashMap<String, ProblemFactory>) -> Vec<String> {
+    let mut names: Vec<_> = problems.keys().cloned().collect();
+    names.sort_by_key(|name| (day_number(name), name.clone()));
+    names
+}
+
+// A day can register other ways of solving it as variants, named "dayN-variant".
+// They all share the day's input and should all get the same answers.
+pub fn variant_name(day: &str, variant: &str) -> String {
+    format!("{day}-{variant}")
+}
+
+pub fn day_of(name: &str) -> &str {
+    name.split_once('-').map_or(name, |(day, _)| day)
+}
+
+// The day itself first, then its variants
+pub fn variants(problems: &HashMap<String, ProblemFactory>, day: &str) -> Vec<String> {
+    sorted_names(problems)
+        .into_iter()
+        .filter(|name| day_of(name) == day)
+        .collect()
+}
+
+fn day_number(name: &str) -> usize {
+    name.trim_start_matches("day")
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>()
+        .parse()
+        .unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_number() {
+        assert_eq!(day_number("day1"), 1);
+        assert_eq!(day_number("day19"), 19);
+        assert_eq!(day_number("day3-chumsky"), 3);
+        assert_eq!(day_number("bogus"), usize::MAX);
+    }
+
+    #[test]
+    fn test_selected_parts() {
+        assert_eq!(Part::selected(None), vec![Part::One, Part::Two]);
+        assert_eq!(Part::selected(Some(Part::Two)), vec![Part::Two]);
+    }
+
+    #[test]
+    fn test_sorted_names() {
+        let mut problems: HashMap<String, ProblemFactory> = HashMap::new();
+        for name in ["day10", "day3-chumsky", "day2", "day3"] {
+            problems.insert(name.to_string(), |_| unreachable!());
+        }
+        assert_eq!(
+            sorted_names(&problems),
+            vec!["day2", "day3", "day3-chumsky", "day10"]
+        );
+    }
+
+    #[test]
+    fn test_variants() {
+        let mut problems: HashMap<String, ProblemFactory> = HashMap::new();
+        for name in ["day3-chumsky", "day3", "day30", "day4"] {
+            problems.insert(name.to_string(), |_| unreachable!());
+        }
+        assert_eq!(variant_name("day3", "chumsky"), "day3-chumsky");
+        assert_eq!(day_of("day3-chumsky"), "day3");
+        assert_eq!(day_of("day30"), "day30");
+        assert_eq!(variants(&problems, "day3"), vec!["day3", "day3-chumsky"]);
+        assert_eq!(variants(&problems, "day4"), vec!["day4"]);
+    }
 }
@@ -0,0 +1,280 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    coord::Coord,
+    direction::Cardinal,
+    grid::Grid,
+    point::TextPoint,
+    trace::{trace, Level},
+};
+
+// A patch of matching cells joined up/down/left/right
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Region<T> {
+    pub label: T,
+    // In reading order, top to bottom, left to right
+    pub cells: Vec<TextPoint>,
+    pub perimeter: usize,
+    // Straight runs of fence, which is the same as the number of corners
+    pub sides: usize,
+    // Top left and bottom right, inclusive
+    pub bounds: (TextPoint, TextPoint),
+    // The index of the one region that completely surrounds this one
+    pub enclosed_by: Option<usize>,
+}
+
+impl<T> Region<T> {
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+// Every region in the grid, in the order their first cell is read. Each cell
+// belongs to exactly one.
+pub fn regions<T: PartialEq + Clone>(grid: &Grid<T>) -> Vec<Region<T>> {
+    let ids = label(grid);
+    let mut cells: Vec<Vec<TextPoint>> = Vec::new();
+    for (p, id) in ids.cells() {
+        if *id == cells.len() {
+            cells.push(Vec::new());
+        }
+        cells[*id].push(p);
+    }
+
+    cells
+        .into_iter()
+        .enumerate()
+        .map(|(id, cells)| {
+            let (perimeter, sides) = fence(&ids, id, &cells);
+            let bounds = bounds(&cells);
+            Region {
+                label: grid[cells[0]].clone(),
+                enclosed_by: enclosed_by(&ids, id, bounds),
+                cells,
+                perimeter,
+                sides,
+                bounds,
+            }
+        })
+        .collect()
+}
+
+// Which region each cell is in, numbered in reading order. A flood fill with
+// our own stack, so one huge region can't overflow the real one.
+fn label<T: PartialEq>(grid: &Grid<T>) -> Grid<usize> {
+    let corner = grid.corner();
+    let mut ids = Grid::filled(grid.width(), grid.height(), usize::MAX);
+    let mut next = 0;
+
+    for start in grid.points() {
+        if ids[start] != usize::MAX {
+            continue;
+        }
+
+        ids[start] = next;
+        let mut stack = vec![start];
+        while let Some(p) = stack.pop() {
+            for (_, n) in p.neighbours4(corner) {
+                if ids[n] == usize::MAX && grid[n] == grid[p] {
+                    trace!(Level::Trace, "{n:?} joins region {next}");
+                    ids[n] = next;
+                    stack.push(n);
+                }
+            }
+        }
+        next += 1;
+    }
+
+    ids
+}
+
+fn same(ids: &Grid<usize>, id: usize, c: Coord) -> bool {
+    c.within(ids.corner()).is_some_and(|p| ids[p] == id)
+}
+
+// Fence length and number of sides. Each corner of a region is a cell that
+// sticks out (neither neighbour round the corner is in) or one that fills an
+// inside bend (both are, but not the cell diagonally between them).
+fn fence(ids: &Grid<usize>, id: usize, cells: &[TextPoint]) -> (usize, usize) {
+    let mut perimeter = 0;
+    let mut corners = 0;
+
+    for p in cells {
+        let here = Coord::from(*p);
+        for d in Cardinal::ALL {
+            let d2 = d.rotate_right();
+            let a = same(ids, id, here + d.delta());
+            let b = same(ids, id, here + d2.delta());
+            let between = same(ids, id, here + d.delta() + d2.delta());
+
+            if !a {
+                perimeter += 1;
+            }
+            if (!a && !b) || (a && b && !between) {
+                corners += 1;
+            }
+        }
+    }
+
+    (perimeter, corners)
+}
+
+fn bounds(cells: &[TextPoint]) -> (TextPoint, TextPoint) {
+    let rows = cells.iter().map(|p| p.row);
+    let cols = cells.iter().map(|p| p.col);
+    (
+        TextPoint {
+            row: rows.clone().min().unwrap(),
+            col: cols.clone().min().unwrap(),
+        },
+        TextPoint {
+            row: rows.max().unwrap(),
+            col: cols.max().unwrap(),
+        },
+    )
+}
+
+// Flood in from just outside the bounding box, which finds everything that
+// touches the region from outside without getting into its holes. If that's
+// all one other region then it's the one we're inside. Anything on the edge of
+// the map can't be enclosed.
+fn enclosed_by(ids: &Grid<usize>, id: usize, bounds: (TextPoint, TextPoint)) -> Option<usize> {
+    let (top_left, bottom_right) = bounds;
+    let corner = ids.corner();
+    if top_left.row == 0
+        || top_left.col == 0
+        || bottom_right.row == corner.row
+        || bottom_right.col == corner.col
+    {
+        return None;
+    }
+
+    let top_left = TextPoint {
+        row: top_left.row - 1,
+        col: top_left.col - 1,
+    };
+    let bottom_right = TextPoint {
+        row: bottom_right.row + 1,
+        col: bottom_right.col + 1,
+    };
+    let in_box = |p: &TextPoint| {
+        (top_left.row..=bottom_right.row).contains(&p.row)
+            && (top_left.col..=bottom_right.col).contains(&p.col)
+    };
+
+    let mut stack: Vec<TextPoint> = (top_left.row..=bottom_right.row)
+        .flat_map(|row| (top_left.col..=bottom_right.col).map(move |col| TextPoint { row, col }))
+        .filter(|p| {
+            p.row == top_left.row
+                || p.row == bottom_right.row
+                || p.col == top_left.col
+                || p.col == bottom_right.col
+        })
+        .collect();
+    let mut seen: BTreeSet<TextPoint> = stack.iter().copied().collect();
+    let mut outside = None;
+
+    while let Some(p) = stack.pop() {
+        for (_, n) in p.neighbours4(corner) {
+            if ids[n] == id {
+                match outside {
+                    None => outside = Some(ids[p]),
+                    Some(o) if o != ids[p] => return None,
+                    _ => {}
+                }
+            } else if in_box(&n) && seen.insert(n) {
+                stack.push(n);
+            }
+        }
+    }
+
+    outside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_point(row: usize, col: usize) -> TextPoint {
+        TextPoint { row, col }
+    }
+
+    #[test]
+    fn test_regions() {
+        let g = Grid::parse("AAAA\nBBCD\nBBCC\nEEEC").unwrap();
+        let rs = regions(&g);
+
+        let summary: Vec<_> = rs
+            .iter()
+            .map(|r| (r.label, r.area(), r.perimeter, r.sides))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (b'A', 4, 10, 4),
+                (b'B', 4, 8, 4),
+                (b'C', 4, 10, 8),
+                (b'D', 1, 4, 4),
+                (b'E', 3, 8, 4),
+            ]
+        );
+        assert_eq!(rs[2].bounds, (text_point(1, 2), text_point(3, 3)));
+        assert!(rs.iter().all(|r| r.enclosed_by.is_none()));
+    }
+
+    #[test]
+    fn test_separate_patches() {
+        let g = Grid::parse("ABA\nAAB").unwrap();
+        let cells: Vec<_> = regions(&g).into_iter().map(|r| r.cells).collect();
+
+        assert_eq!(
+            cells,
+            vec![
+                vec![text_point(0, 0), text_point(1, 0), text_point(1, 1)],
+                vec![text_point(0, 1)],
+                vec![text_point(0, 2)],
+                vec![text_point(1, 2)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enclosed() {
+        // An O ring with X inside, and another with Y and Z rings nested round a second X
+        let g = Grid::parse(
+            "OOOOO.OOOOOOO
+OXXXO.OYYYYYO
+OOOOO.OYZZZYO
+......OYZXZYO
+......OYZZZYO
+......OYYYYYO
+......OOOOOOO",
+        )
+        .unwrap();
+        let rs = regions(&g);
+        let find = |label: u8| rs.iter().position(|r| r.label == label).unwrap();
+
+        assert_eq!(rs[find(b'X')].enclosed_by, Some(find(b'O')));
+        assert_eq!(rs[find(b'O')].enclosed_by, None);
+        let second_ring = rs.iter().rposition(|r| r.label == b'O').unwrap();
+        assert_eq!(rs[find(b'Y')].enclosed_by, Some(second_ring));
+        assert_eq!(rs[find(b'Z')].enclosed_by, Some(find(b'Y')));
+
+        // Inside the Z ring, not the Y one
+        let inner = rs.iter().rposition(|r| r.label == b'X').unwrap();
+        assert_eq!(rs[inner].enclosed_by, Some(find(b'Z')));
+    }
+
+    #[test]
+    fn test_sides_with_holes() {
+        // The puzzle's E example, and the A and B one with the diagonal touch
+        let g = Grid::parse("EEEEE\nEXXXX\nEEEEE\nEXXXX\nEEEEE").unwrap();
+        let rs = regions(&g);
+        assert_eq!(rs[0].sides, 12);
+
+        let g = Grid::parse("AAAAAA\nAAABBA\nAAABBA\nABBAAA\nABBAAA\nAAAAAA").unwrap();
+        let rs = regions(&g);
+        assert_eq!(rs[0].area() * rs[0].sides, 28 * 12);
+        assert_eq!(rs[1].enclosed_by, Some(0));
+    }
+}
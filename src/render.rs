@@ -0,0 +1,430 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    grid::Grid,
+    point::TextPoint,
+    trace::{self, trace, Level},
+};
+
+// How traced grids get drawn, picked with `--trace-format`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    // Text with 24-bit colour escapes, for a terminal
+    Ansi,
+    // Images, one square of pixels per cell. The legend only goes in the text ones.
+    Ppm,
+    Png,
+}
+
+impl Format {
+    pub fn parse(s: &str) -> Option<Format> {
+        match s {
+            "text" => Some(Format::Text),
+            "ansi" => Some(Format::Ansi),
+            "ppm" => Some(Format::Ppm),
+            "png" => Some(Format::Png),
+            _ => None,
+        }
+    }
+
+    fn is_image(self) -> bool {
+        matches!(self, Format::Ppm | Format::Png)
+    }
+}
+
+// How many pixels across each cell is in an image
+const SCALE: usize = 4;
+
+// How many images have been traced so far, so each gets its own file
+static PICTURES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const RED: Rgb = Rgb(230, 50, 50);
+    pub const GREEN: Rgb = Rgb(60, 200, 60);
+    pub const YELLOW: Rgb = Rgb(240, 220, 50);
+
+    // Cold for the smallest values, hot for the biggest
+    fn heat(value: usize, min: usize, max: usize) -> Rgb {
+        let t = if max > min {
+            (value - min) as f64 / (max - min) as f64
+        } else {
+            0.0
+        };
+        Rgb((255.0 * t) as u8, 40, (255.0 * (1.0 - t)) as u8)
+    }
+
+    // What the puzzle's own characters look like in an image
+    fn of_cell(c: u8) -> Rgb {
+        match c {
+            b'#' => Rgb(110, 110, 110),
+            b'.' => Rgb(0, 0, 0),
+            _ => Rgb(200, 200, 200),
+        }
+    }
+}
+
+// Something drawn on top of a cell, with a label for the legend
+#[derive(Clone, Debug)]
+struct Layer {
+    glyph: u8,
+    colour: Rgb,
+    label: String,
+    points: Vec<TextPoint>,
+}
+
+// A grid with things drawn over it. Markers go on top of paths, which go on
+// top of the heatmap, which goes on top of the grid itself.
+pub struct Renderer<'a> {
+    grid: &'a Grid<u8>,
+    heat: HashMap<TextPoint, usize>,
+    paths: Vec<Layer>,
+    markers: Vec<Layer>,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(grid: &'a Grid<u8>) -> Self {
+        Renderer {
+            grid,
+            heat: HashMap::new(),
+            paths: Vec::new(),
+            markers: Vec::new(),
+        }
+    }
+
+    // A number per cell, like the cost to get there. Cells without one are left alone.
+    pub fn heatmap(mut self, values: impl IntoIterator<Item = (TextPoint, usize)>) -> Self {
+        self.heat.extend(values);
+        self
+    }
+
+    pub fn path(
+        mut self,
+        glyph: u8,
+        colour: Rgb,
+        label: &str,
+        points: impl IntoIterator<Item = TextPoint>,
+    ) -> Self {
+        self.paths.push(Layer {
+            glyph,
+            colour,
+            label: label.to_string(),
+            points: points.into_iter().collect(),
+        });
+        self
+    }
+
+    pub fn marker(mut self, at: TextPoint, glyph: u8, colour: Rgb, label: &str) -> Self {
+        self.markers.push(Layer {
+            glyph,
+            colour,
+            label: label.to_string(),
+            points: vec![at],
+        });
+        self
+    }
+
+    // The top layer at `p`, if there's anything over the grid there
+    fn layer_at(&self, p: TextPoint) -> Option<&Layer> {
+        self.markers
+            .iter()
+            .rev()
+            .chain(self.paths.iter().rev())
+            .find(|l| l.points.contains(&p))
+    }
+
+    fn heat_range(&self) -> (usize, usize) {
+        let min = self.heat.values().copied().min().unwrap_or(0);
+        let max = self.heat.values().copied().max().unwrap_or(0);
+        (min, max)
+    }
+
+    pub fn render(&self, format: Format) -> Vec<u8> {
+        match format {
+            Format::Text => self.text(false).into_bytes(),
+            Format::Ansi => self.text(true).into_bytes(),
+            Format::Ppm => self.ppm(),
+            Format::Png => self.png(),
+        }
+    }
+
+    // Draws in the traced format, but only if `level` is being traced. Text
+    // goes to stderr with the rest of the trace, and images to a new file.
+    pub fn trace(&self, level: Level) {
+        if !trace::enabled(level) {
+            return;
+        }
+
+        let format = trace::format();
+        if !format.is_image() {
+            // Debug output, so there's nothing useful to do if stderr has gone away
+            let _ = io::stderr().write_all(&self.render(format));
+            return;
+        }
+
+        let n = PICTURES.fetch_add(1, Ordering::Relaxed) + 1;
+        let ext = if format == Format::Ppm { "ppm" } else { "png" };
+        let path = PathBuf::from(format!("trace-{n}.{ext}"));
+        match self.save(&path, format) {
+            Ok(()) => trace!(level, "saved {}", path.display()),
+            Err(e) => trace!(level, "couldn't save {}: {e}", path.display()),
+        }
+    }
+
+    pub fn save(&self, path: &Path, format: Format) -> io::Result<()> {
+        fs::write(path, self.render(format))
+    }
+
+    fn text(&self, colour: bool) -> String {
+        // Heatmaps line numbers up in columns, like the printers they replaced did
+        let (min, max) = self.heat_range();
+        let width = if self.heat.is_empty() {
+            1
+        } else {
+            max.to_string().len()
+        };
+
+        let mut out = String::new();
+        for (row, cells) in self.grid.rows().enumerate() {
+            for (col, c) in cells.iter().enumerate() {
+                let p = TextPoint { row, col };
+                if width > 1 && col > 0 {
+                    out.push(' ');
+                }
+
+                let (text, fg, bg) = match (self.layer_at(p), self.heat.get(&p)) {
+                    (Some(layer), heat) => (
+                        (layer.glyph as char).to_string(),
+                        Some(layer.colour),
+                        heat.map(|v| Rgb::heat(*v, min, max)),
+                    ),
+                    (None, Some(v)) if !colour => (v.to_string(), None, None),
+                    (None, Some(v)) => (
+                        (*c as char).to_string(),
+                        None,
+                        Some(Rgb::heat(*v, min, max)),
+                    ),
+                    (None, None) => ((*c as char).to_string(), None, None),
+                };
+
+                if colour {
+                    if let Some(Rgb(r, g, b)) = fg {
+                        out.push_str(&format!("\x1b[38;2;{r};{g};{b}m"));
+                    }
+                    if let Some(Rgb(r, g, b)) = bg {
+                        out.push_str(&format!("\x1b[48;2;{r};{g};{b}m"));
+                    }
+                }
+                out.push_str(&format!("{text:>width$}"));
+                if colour && (fg.is_some() || bg.is_some()) {
+                    out.push_str("\x1b[0m");
+                }
+            }
+            out.push('\n');
+        }
+
+        for layer in self.paths.iter().chain(self.markers.iter()) {
+            let glyph = layer.glyph as char;
+            if colour {
+                let Rgb(r, g, b) = layer.colour;
+                out.push_str(&format!("\x1b[38;2;{r};{g};{b}m{glyph}\x1b[0m"));
+            } else {
+                out.push(glyph);
+            }
+            out.push_str(&format!(" {}\n", layer.label));
+        }
+        if !self.heat.is_empty() {
+            out.push_str(&format!("heat {min}..={max}\n"));
+        }
+
+        out
+    }
+
+    // Row by row, `SCALE` pixels for each cell each way
+    fn pixels(&self) -> (usize, usize, Vec<u8>) {
+        let (min, max) = self.heat_range();
+        let (width, height) = (self.grid.width() * SCALE, self.grid.height() * SCALE);
+
+        let mut pixels = Vec::with_capacity(width * height * 3);
+        for row in 0..height {
+            for col in 0..width {
+                let p = TextPoint {
+                    row: row / SCALE,
+                    col: col / SCALE,
+                };
+                let Rgb(r, g, b) = match (self.layer_at(p), self.heat.get(&p)) {
+                    (Some(layer), _) => layer.colour,
+                    (None, Some(v)) => Rgb::heat(*v, min, max),
+                    (None, None) => Rgb::of_cell(self.grid[p]),
+                };
+                pixels.extend([r, g, b]);
+            }
+        }
+
+        (width, height, pixels)
+    }
+
+    fn ppm(&self) -> Vec<u8> {
+        let (width, height, pixels) = self.pixels();
+        let mut out = format!("P6\n{width} {height}\n255\n").into_bytes();
+        out.extend(pixels);
+        out
+    }
+
+    // Uncompressed, which is fine for puzzle sized grids and saves a dependency
+    fn png(&self) -> Vec<u8> {
+        let (width, height, pixels) = self.pixels();
+
+        let mut ihdr = Vec::new();
+        ihdr.extend((width as u32).to_be_bytes());
+        ihdr.extend((height as u32).to_be_bytes());
+        // 8 bits per channel, RGB, no interlacing
+        ihdr.extend([8, 2, 0, 0, 0]);
+
+        // Every scanline starts with a "no filter" byte
+        let mut raw = Vec::with_capacity((width * 3 + 1) * height);
+        for line in pixels.chunks(width * 3).filter(|l| !l.is_empty()) {
+            raw.push(0);
+            raw.extend(line);
+        }
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut out, b"IHDR", &ihdr);
+        png_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(kind.iter().chain(data));
+    out.extend(crc.to_be_bytes());
+}
+
+// A zlib stream made of deflate's "stored" blocks, which are just the bytes
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32<'a>(data: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = u32::MAX;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_point(row: usize, col: usize) -> TextPoint {
+        TextPoint { row, col }
+    }
+
+    #[test]
+    fn test_text_layers() {
+        let g = Grid::parse("...\n.#.\n...").unwrap();
+        let out = Renderer::new(&g)
+            .path(
+                b'O',
+                Rgb::GREEN,
+                "route",
+                [text_point(0, 0), text_point(0, 1)],
+            )
+            .marker(text_point(0, 0), b'S', Rgb::RED, "start")
+            .render(Format::Text);
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "SO.\n.#.\n...\nO route\nS start\n"
+        );
+    }
+
+    #[test]
+    fn test_text_heatmap() {
+        let g = Grid::parse("..\n#.").unwrap();
+        let out = Renderer::new(&g)
+            .heatmap([(text_point(0, 0), 0), (text_point(0, 1), 10)])
+            .render(Format::Text);
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            " 0 10\n #  .\nheat 0..=10\n"
+        );
+    }
+
+    #[test]
+    fn test_ansi() {
+        let g = Grid::parse("..").unwrap();
+        let out = Renderer::new(&g)
+            .marker(text_point(0, 1), b'E', Rgb(1, 2, 3), "end")
+            .render(Format::Ansi);
+
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with(".\x1b[38;2;1;2;3mE\x1b[0m\n"));
+    }
+
+    #[test]
+    fn test_images() {
+        let g = Grid::parse("#.\n..").unwrap();
+        let r = Renderer::new(&g);
+
+        let ppm = r.render(Format::Ppm);
+        assert!(ppm.starts_with(b"P6\n8 8\n255\n"));
+        assert_eq!(ppm.len(), b"P6\n8 8\n255\n".len() + 8 * 8 * 3);
+
+        let png = r.render(Format::Png);
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+}
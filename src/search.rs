@@ -0,0 +1,452 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    fmt::Debug,
+    hash::Hash,
+};
+
+use crate::trace::{trace, Level};
+
+// Anything we can search for a cheapest way through. A state is wherever we
+// are plus anything else that changes where we can go next, like which way
+// we're facing. States are `Debug` so searches can be traced.
+pub trait Graph {
+    type State: Clone + Eq + Hash + Debug;
+
+    // Where we can go from `state`, and what it costs to get there. Steps can
+    // be free, but a loop of them means there's no end of cheapest paths.
+    fn neighbours(&self, state: &Self::State) -> impl Iterator<Item = (usize, Self::State)>;
+
+    // A guess at the cost to the goal for A*. It mustn't ever be more than the
+    // real cost, and the default of 0 is the same as Dijkstra.
+    fn heuristic(&self, _state: &Self::State) -> usize {
+        0
+    }
+}
+
+// What a search found. Every state it looked at has a cost, and a state
+// remembers all of the states it can be reached from at that cost. Following
+// those back from the goals gives every cheapest path, which all come
+// together at the start.
+#[derive(Clone, Debug)]
+pub struct Search<S> {
+    start: S,
+    dist: HashMap<S, usize>,
+    prev: HashMap<S, Vec<S>>,
+    goals: Vec<S>,
+}
+
+impl<S: Clone + Eq + Hash> Search<S> {
+    fn new(start: S) -> Self {
+        Search {
+            dist: HashMap::from([(start.clone(), 0)]),
+            prev: HashMap::new(),
+            goals: Vec::new(),
+            start,
+        }
+    }
+
+    // The cheapest way to a goal, or None if there isn't one
+    pub fn cost(&self) -> Option<usize> {
+        self.goals.first().map(|g| self.dist[g])
+    }
+
+    // Every goal state that can be reached for `cost`
+    pub fn goals(&self) -> &[S] {
+        &self.goals
+    }
+
+    // The cheapest cost we found for everything we looked at
+    pub fn distances(&self) -> impl Iterator<Item = (&S, usize)> {
+        self.dist.iter().map(|(s, d)| (s, *d))
+    }
+
+    // The states just before `state` on its cheapest ways here
+    pub fn predecessors(&self, state: &S) -> &[S] {
+        self.prev.get(state).map_or(&[], Vec::as_slice)
+    }
+
+    // One cheapest way from the start to a goal, including both
+    pub fn path(&self) -> Option<Vec<S>> {
+        let mut path = vec![self.goals.first()?.clone()];
+        while let Some(p) = self.predecessors(path.last().unwrap()).first() {
+            path.push(p.clone());
+        }
+        path.reverse();
+        debug_assert!(path[0] == self.start);
+        Some(path)
+    }
+
+    // How many different cheapest paths there are, to any of the goals. None
+    // if there's no end to them, which only happens if a loop costs nothing.
+    pub fn count_paths(&self) -> Option<usize> {
+        // Free steps mean costs can tie along a path, so go in topological
+        // order: a state is counted once everything before it has been
+        let states = self.on_best_paths_ref();
+        let mut waiting: HashMap<&S, usize> = HashMap::new();
+        let mut next: HashMap<&S, Vec<&S>> = HashMap::new();
+        for s in states.iter() {
+            waiting.insert(s, self.predecessors(s).len());
+            for p in self.predecessors(s) {
+                next.entry(p).or_default().push(s);
+            }
+        }
+
+        let mut ways: HashMap<&S, usize> = HashMap::new();
+        let mut ready: Vec<&S> = states.iter().copied().filter(|s| waiting[s] == 0).collect();
+        for s in ready.iter() {
+            ways.insert(s, 1);
+        }
+        let mut counted = 0;
+        while let Some(s) = ready.pop() {
+            counted += 1;
+            for n in next.get(s).into_iter().flatten() {
+                *ways.entry(n).or_default() += ways[s];
+                let w = waiting.get_mut(n).unwrap();
+                *w -= 1;
+                if *w == 0 {
+                    ready.push(n);
+                }
+            }
+        }
+
+        // Anything left over is waiting on itself round a loop
+        (counted == states.len()).then(|| self.goals.iter().map(|g| ways[g]).sum())
+    }
+
+    // Every cheapest path from the start to a goal, one at a time
+    pub fn paths(&self) -> Paths<'_, S> {
+        Paths {
+            search: self,
+            stack: self.goals.iter().map(|g| vec![g.clone()]).collect(),
+        }
+    }
+
+    // Every state that's on at least one cheapest path
+    pub fn on_best_paths(&self) -> HashSet<S> {
+        self.on_best_paths_ref().into_iter().cloned().collect()
+    }
+
+    fn on_best_paths_ref(&self) -> HashSet<&S> {
+        let mut seen = HashSet::new();
+        let mut stack: Vec<&S> = self.goals.iter().collect();
+        while let Some(s) = stack.pop() {
+            if seen.insert(s) {
+                stack.extend(self.predecessors(s));
+            }
+        }
+        seen
+    }
+
+    fn trace_summary(&self) {
+        match self.cost() {
+            Some(cost) => trace!(
+                Level::Info,
+                "{} goal(s) for {cost}, after looking at {} states",
+                self.goals.len(),
+                self.dist.len()
+            ),
+            None => trace!(
+                Level::Info,
+                "no goals, after looking at {} states",
+                self.dist.len()
+            ),
+        }
+    }
+
+    // Found another way to `next` through `from`. Returns whether it's
+    // cheaper than anything before, so `next` has to be looked at (again).
+    fn relax(&mut self, from: &S, next: S, cost: usize) -> bool {
+        match self.dist.get(&next) {
+            Some(d) if cost > *d => false,
+            Some(d) if cost == *d => {
+                // Two moves from the same place that end up the same are one way here
+                let prev = self.prev.entry(next).or_default();
+                if !prev.contains(from) {
+                    prev.push(from.clone());
+                }
+                false
+            }
+            _ => {
+                self.dist.insert(next.clone(), cost);
+                self.prev.insert(next, vec![from.clone()]);
+                true
+            }
+        }
+    }
+}
+
+// Walks back from the goals, keeping each half built path on a stack, so only
+// the ones we've asked for so far get built
+pub struct Paths<'a, S> {
+    search: &'a Search<S>,
+    stack: Vec<Vec<S>>,
+}
+
+impl<S: Clone + Eq + Hash> Iterator for Paths<'_, S> {
+    type Item = Vec<S>;
+
+    fn next(&mut self) -> Option<Vec<S>> {
+        while let Some(mut path) = self.stack.pop() {
+            let last = path.last().unwrap();
+            if *last == self.search.start {
+                path.reverse();
+                return Some(path);
+            }
+            for p in self.search.predecessors(last) {
+                let mut longer = path.clone();
+                longer.push(p.clone());
+                self.stack.push(longer);
+            }
+        }
+        None
+    }
+}
+
+struct Entry<S> {
+    priority: usize,
+    cost: usize,
+    state: S,
+}
+
+// The priority queue depends on `Ord`. Flip the ordering so the queue is a
+// min-heap, and only look at the priority, since the states don't have to be
+// ordered at all.
+impl<S> Ord for Entry<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+impl<S> PartialOrd for Entry<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S> PartialEq for Entry<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<S> Eq for Entry<S> {}
+
+fn best_first<G: Graph>(
+    graph: &G,
+    start: G::State,
+    is_goal: impl Fn(&G::State) -> bool,
+    heuristic: impl Fn(&G::State) -> usize,
+) -> Search<G::State> {
+    let mut search = Search::new(start.clone());
+    let mut heap = BinaryHeap::from([Entry {
+        priority: heuristic(&start),
+        cost: 0,
+        state: start,
+    }]);
+    let mut best = None;
+
+    while let Some(Entry {
+        priority,
+        cost,
+        state,
+    }) = heap.pop()
+    {
+        // Everything left is worse than what we've already found
+        if best.is_some_and(|b| priority > b) {
+            break;
+        }
+
+        // We may have already found a better way here
+        if cost > search.dist[&state] {
+            continue;
+        }
+        trace!(Level::Trace, "looking at {state:?}, costing {cost}");
+
+        // Stop at goals, but keep going for any others that cost the same
+        if is_goal(&state) {
+            best = Some(cost);
+            search.goals.push(state);
+            continue;
+        }
+
+        for (step, next) in graph.neighbours(&state) {
+            if search.relax(&state, next.clone(), cost + step) {
+                heap.push(Entry {
+                    priority: cost + step + heuristic(&next),
+                    cost: cost + step,
+                    state: next,
+                });
+            }
+        }
+    }
+
+    search.trace_summary();
+    search
+}
+
+// Cheapest first, stopping once the cheapest goals are found
+pub fn dijkstra<G: Graph>(
+    graph: &G,
+    start: G::State,
+    is_goal: impl Fn(&G::State) -> bool,
+) -> Search<G::State> {
+    best_first(graph, start, is_goal, |_| 0)
+}
+
+// Like `dijkstra` but heads towards the goal, using the graph's heuristic
+pub fn astar<G: Graph>(
+    graph: &G,
+    start: G::State,
+    is_goal: impl Fn(&G::State) -> bool,
+) -> Search<G::State> {
+    best_first(graph, start, is_goal, |s| graph.heuristic(s))
+}
+
+// Fewest steps, whatever each one costs
+pub fn bfs<G: Graph>(
+    graph: &G,
+    start: G::State,
+    is_goal: impl Fn(&G::State) -> bool,
+) -> Search<G::State> {
+    let mut search = Search::new(start.clone());
+    let mut queue = VecDeque::from([(0, start)]);
+    let mut best = None;
+
+    while let Some((steps, state)) = queue.pop_front() {
+        if best.is_some_and(|b| steps > b) {
+            break;
+        }
+
+        trace!(Level::Trace, "looking at {state:?}, {steps} steps away");
+        if is_goal(&state) {
+            best = Some(steps);
+            search.goals.push(state);
+            continue;
+        }
+
+        for (_, next) in graph.neighbours(&state) {
+            if search.relax(&state, next.clone(), steps + 1) {
+                queue.push_back((steps + 1, next));
+            }
+        }
+    }
+
+    search.trace_summary();
+    search
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Numbers up to 20, where you can add one for 1 or double for 3
+    struct Numbers;
+
+    impl Graph for Numbers {
+        type State = u32;
+
+        fn neighbours(&self, n: &u32) -> impl Iterator<Item = (usize, u32)> {
+            [(1, n + 1), (3, n * 2)]
+                .into_iter()
+                .filter(|(_, m)| *m <= 20)
+        }
+
+        fn heuristic(&self, n: &u32) -> usize {
+            // Only used to get to 10, and never more than the real cost there
+            10u32.saturating_sub(*n).min(3) as usize
+        }
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let search = dijkstra(&Numbers, 1, |n| *n == 10);
+        assert_eq!(search.cost(), Some(7));
+        assert_eq!(search.path(), Some(vec![1, 2, 3, 4, 5, 10]));
+        assert_eq!(search.goals(), &[10]);
+
+        let search = dijkstra(&Numbers, 1, |n| *n == 0);
+        assert_eq!(search.cost(), None);
+        assert_eq!(search.path(), None);
+    }
+
+    #[test]
+    fn test_astar() {
+        let search = astar(&Numbers, 1, |n| *n == 10);
+        assert_eq!(search.cost(), Some(7));
+        assert_eq!(search.path().unwrap().last(), Some(&10));
+    }
+
+    #[test]
+    fn test_bfs() {
+        let search = bfs(&Numbers, 1, |n| *n == 10);
+        assert_eq!(search.cost(), Some(4));
+        assert_eq!(search.path().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_predecessors() {
+        // 6 is 5 + 1 or 3 * 2, both for 5 all told
+        let search = dijkstra(&Numbers, 1, |n| *n == 6);
+        assert_eq!(search.cost(), Some(5));
+        let mut prev = search.predecessors(&6).to_vec();
+        prev.sort();
+        assert_eq!(prev, vec![3, 5]);
+    }
+
+    #[test]
+    fn test_all_best_paths() {
+        let search = dijkstra(&Numbers, 1, |n| *n == 6);
+        assert_eq!(search.count_paths(), Some(2));
+
+        let mut paths: Vec<_> = search.paths().collect();
+        paths.sort();
+        assert_eq!(paths, vec![vec![1, 2, 3, 4, 5, 6], vec![1, 2, 3, 6]]);
+
+        assert_eq!(search.on_best_paths(), HashSet::from([1, 2, 3, 4, 5, 6]));
+    }
+
+    #[test]
+    fn test_all_best_paths_many_goals() {
+        // Both cost 6, 7 by either way to 6 and 8 by doubling 4
+        let search = dijkstra(&Numbers, 1, |n| *n == 7 || *n == 8);
+        assert_eq!(search.cost(), Some(6));
+        let mut goals = search.goals().to_vec();
+        goals.sort();
+        assert_eq!(goals, vec![7, 8]);
+        assert_eq!(search.count_paths(), Some(3));
+        assert_eq!(search.paths().count(), 3);
+    }
+
+    // 0, 1 and 2 are all free to get between going up, and 3 costs 1 from
+    // any of them. With `back`, 1 can go back to 0 for free as well.
+    struct Free {
+        back: bool,
+    }
+
+    impl Graph for Free {
+        type State = u32;
+
+        fn neighbours(&self, n: &u32) -> impl Iterator<Item = (usize, u32)> {
+            let up = (*n + 1..3).map(|m| (0, m));
+            let back = (self.back && *n == 1).then_some((0, 0));
+            let done = (*n < 3).then_some((1, 3));
+            up.chain(back).chain(done)
+        }
+    }
+
+    #[test]
+    fn test_free_steps() {
+        let search = dijkstra(&Free { back: false }, 0, |n| *n == 3);
+        assert_eq!(search.cost(), Some(1));
+        // Straight to 3, or by way of 1, 2 or both
+        assert_eq!(search.count_paths(), Some(4));
+        assert_eq!(search.paths().count(), 4);
+
+        // Round and round 0 and 1 as many times as you like
+        let search = dijkstra(&Free { back: true }, 0, |n| *n == 3);
+        assert_eq!(search.cost(), Some(1));
+        assert_eq!(search.on_best_paths(), HashSet::from([0, 1, 2, 3]));
+        assert_eq!(search.count_paths(), None);
+    }
+}
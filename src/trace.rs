@@ -0,0 +1,137 @@
+use std::{cell::Cell, fmt::Display, sync::OnceLock};
+
+use crate::{problem, render::Format};
+
+// How much a day says about what it's doing while it works, least first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    // A line or so per search
+    Info,
+    // Pictures of what it found
+    Debug,
+    // Every step, which is a lot on the real input
+    Trace,
+}
+
+// What `--trace <day>` on its own turns on
+pub const DEFAULT_LEVEL: Level = Level::Debug;
+
+impl Level {
+    pub fn parse(s: &str) -> Option<Level> {
+        match s {
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Level::Info => write!(f, "info"),
+            Level::Debug => write!(f, "debug"),
+            Level::Trace => write!(f, "trace"),
+        }
+    }
+}
+
+// The days asked for on the command line, set once before anything's solved
+static TRACED: OnceLock<Vec<(String, Level)>> = OnceLock::new();
+
+thread_local! {
+    // How much the problem being solved right now can say, if anything
+    static ACTIVE: Cell<Option<Level>> = const { Cell::new(None) };
+}
+
+// How traced grids are drawn, set along with TRACED
+static FORMAT: OnceLock<Format> = OnceLock::new();
+
+pub fn configure(traced: Vec<(String, Level)>, format: Format) {
+    // Only main calls this, so they can't already be set
+    let _ = TRACED.set(traced);
+    let _ = FORMAT.set(format);
+}
+
+pub fn format() -> Format {
+    FORMAT.get().copied().unwrap_or(Format::Text)
+}
+
+// How much `name` can say. Tracing a day traces all of its variants too, so
+// `day3` covers `day3-chumsky`.
+pub fn level_for(traced: &[(String, Level)], name: &str) -> Option<Level> {
+    traced
+        .iter()
+        .filter(|(day, _)| day == name || day == problem::day_of(name))
+        .map(|(_, level)| *level)
+        .max()
+}
+
+// Runs `f` with whatever tracing was asked for on `name`
+pub fn for_problem<T>(name: &str, f: impl FnOnce() -> T) -> T {
+    let level = TRACED.get().and_then(|traced| level_for(traced, name));
+    with_level(level, f)
+}
+
+// Runs `f` with everything up to `level` traced, or nothing for None
+pub fn with_level<T>(level: Option<Level>, f: impl FnOnce() -> T) -> T {
+    let before = ACTIVE.replace(level);
+    let res = f();
+    ACTIVE.set(before);
+    res
+}
+
+pub fn enabled(level: Level) -> bool {
+    ACTIVE.get().is_some_and(|active| level <= active)
+}
+
+// Like `eprintln!`, but only if `level` is being traced. The arguments aren't
+// even worked out otherwise, so they can be as slow as they like. Exported
+// under another name so it can live in this module as `trace::trace`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __trace {
+    ($level:expr, $($arg:tt)+) => {{
+        let level = $level;
+        if $crate::trace::enabled(level) {
+            eprintln!("[{level}] {}", format_args!($($arg)+));
+        }
+    }};
+}
+
+pub use crate::__trace as trace;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_for() {
+        let traced = vec![
+            ("day3".to_string(), Level::Info),
+            ("day3-chumsky".to_string(), Level::Trace),
+        ];
+
+        assert_eq!(level_for(&traced, "day3"), Some(Level::Info));
+        assert_eq!(level_for(&traced, "day3-chumsky"), Some(Level::Trace));
+        assert_eq!(level_for(&traced, "day4"), None);
+    }
+
+    #[test]
+    fn test_with_level() {
+        assert!(!enabled(Level::Info));
+
+        with_level(Some(Level::Debug), || {
+            assert!(enabled(Level::Info));
+            assert!(enabled(Level::Debug));
+            assert!(!enabled(Level::Trace));
+
+            // Off for just the inside
+            with_level(None, || assert!(!enabled(Level::Info)));
+            assert!(enabled(Level::Debug));
+        });
+
+        assert!(!enabled(Level::Info));
+    }
+}
@@ -0,0 +1,199 @@
+use std::{collections::HashMap, fmt::Display, path::Path};
+
+use crate::{
+    answer::Answer,
+    error::Error,
+    error::Result,
+    problem::{day_of, Part},
+};
+
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+// Known answers, keyed by problem name and part. The file looks like
+//
+//     [day17]
+//     part1 = "1,5,0,3,7,3,0,3,1"
+//     part2 = 105981155568026
+//
+// and a problem or part that isn't there just can't be checked. Variants are
+// checked against their day's answers unless they have their own.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Answers(HashMap<(String, Part), Answer>);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Fail { expected: Answer },
+    Unknown,
+    // The problem doesn't have an answer for this part yet, which isn't wrong
+    Unsolved,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Fail { expected } => write!(f, "FAIL (expected {expected})"),
+            Outcome::Unknown => write!(f, "unknown"),
+            Outcome::Unsolved => write!(f, "unsolved"),
+        }
+    }
+}
+
+fn to_answer(value: &toml::Value) -> Option<Answer> {
+    match value {
+        toml::Value::Integer(n) => Some(Answer::from(*n)),
+        toml::Value::String(s) => Some(Answer::from(s.as_str())),
+        toml::Value::Array(v) => v
+            .iter()
+            .map(to_answer)
+            .collect::<Option<Vec<_>>>()
+            .map(Answer::List),
+        _ => None,
+    }
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Self> {
+        let table: toml::Table = text
+            .parse()
+            .map_err(|e| Error::input(format!("bad answers file: {e}")))?;
+
+        let mut answers = HashMap::new();
+        for (name, parts) in table.iter() {
+            let parts = parts.as_table().ok_or_else(|| {
+                Error::input(format!("`{name}` in answers file should be a table"))
+            })?;
+
+            for (key, value) in parts.iter() {
+                let part = match key.as_str() {
+                    "part1" => Part::One,
+                    "part2" => Part::Two,
+                    _ => {
+                        return Err(Error::input(format!(
+                            "unknown key `{name}.{key}` in answers file"
+                        )))
+                    }
+                };
+                let answer = to_answer(value).ok_or_else(|| {
+                    Error::input(format!(
+                        "`{name}.{key}` should be an integer, string or list"
+                    ))
+                })?;
+                answers.insert((name.clone(), part), answer);
+            }
+        }
+
+        Ok(Answers(answers))
+    }
+
+    // No answers file at all is fine, everything is just unknown
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Answers::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Answers::default()),
+            Err(e) => Err(Error::input(format!(
+                "could not read answers from {}: {e}",
+                path.display()
+            ))),
+        }
+    }
+
+    pub fn check(&self, name: &str, part: Part, got: &Answer) -> Outcome {
+        let expected = self
+            .0
+            .get(&(name.to_string(), part))
+            .or_else(|| self.0.get(&(day_of(name).to_string(), part)));
+        outcome(expected, got)
+    }
+}
+
+pub fn outcome(expected: Option<&Answer>, got: &Answer) -> Outcome {
+    match expected {
+        _ if *got == Answer::Unsolved => Outcome::Unsolved,
+        None => Outcome::Unknown,
+        Some(expected) if matches(expected, got) => Outcome::Pass,
+        Some(expected) => Outcome::Fail {
+            expected: expected.clone(),
+        },
+    }
+}
+
+// A string in the file matches however the answer prints, so list answers can
+// be written the way they're typed into the website
+fn matches(expected: &Answer, got: &Answer) -> bool {
+    match expected {
+        Answer::Str(s) => expected == got || *s == got.to_string(),
+        _ => expected == got,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANSWERS: &str = "
+[day1]
+part1 = 11
+part2 = \"31\"
+
+[day17]
+part1 = \"4,6,3\"
+part2 = [4, 6]
+";
+
+    #[test]
+    fn test_check() {
+        let answers = Answers::parse(ANSWERS).unwrap();
+
+        assert_eq!(
+            answers.check("day1", Part::One, &11usize.into()),
+            Outcome::Pass
+        );
+        assert_eq!(
+            answers.check("day1", Part::Two, &31usize.into()),
+            Outcome::Pass
+        );
+        assert_eq!(
+            answers.check("day17", Part::One, &vec![4, 6, 3].into()),
+            Outcome::Pass
+        );
+        assert_eq!(
+            answers.check("day17", Part::Two, &vec![4, 7].into()),
+            Outcome::Fail {
+                expected: vec![4, 6].into()
+            }
+        );
+        assert_eq!(
+            answers.check("day2", Part::One, &1usize.into()),
+            Outcome::Unknown
+        );
+        assert_eq!(
+            answers.check("day1-fast", Part::One, &11usize.into()),
+            Outcome::Pass
+        );
+        assert_eq!(
+            answers.check("day1", Part::One, &Answer::Unsolved),
+            Outcome::Unsolved
+        );
+        assert_eq!(
+            answers.check("day2", Part::One, &Answer::Unsolved),
+            Outcome::Unsolved
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Answers::parse("day1 = 3").is_err());
+        assert!(Answers::parse("[day1]\npart3 = 3").is_err());
+        assert!(Answers::parse("[day1]\npart1 = 1.5").is_err());
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        assert_eq!(
+            Answers::load(Path::new("data/no-such-answers.toml")),
+            Ok(Answers::default())
+        );
+    }
+}
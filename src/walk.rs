@@ -0,0 +1,157 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::{
+    grid::Grid,
+    point::TextPoint,
+    trace::{trace, Level},
+};
+
+// Walks a grid up/down/left/right, but only where `step(from, to)` allows it,
+// like a trail that has to climb exactly one at a time
+pub struct Walker<'g, T, F> {
+    grid: &'g Grid<T>,
+    step: F,
+}
+
+impl<'g, T, F: Fn(&T, &T) -> bool> Walker<'g, T, F> {
+    pub fn new(grid: &'g Grid<T>, step: F) -> Self {
+        Walker { grid, step }
+    }
+
+    fn next(&self, p: TextPoint) -> impl Iterator<Item = TextPoint> + '_ {
+        p.neighbours4(self.grid.corner())
+            .filter(move |(_, n)| (self.step)(&self.grid[p], &self.grid[*n]))
+            .map(|(_, n)| n)
+    }
+
+    // Everywhere we can get to from `start`, including `start`
+    pub fn reachable(&self, start: TextPoint) -> HashSet<TextPoint> {
+        let mut seen = HashSet::from([start]);
+        let mut stack = vec![start];
+
+        while let Some(p) = stack.pop() {
+            for n in self.next(p) {
+                if seen.insert(n) {
+                    trace!(Level::Trace, "reached {n:?} from {p:?}");
+                    stack.push(n);
+                }
+            }
+        }
+
+        seen
+    }
+
+    // The fewest steps from `start` to each cell, or None if we can't get there
+    pub fn distances(&self, start: TextPoint) -> Grid<Option<usize>> {
+        let mut dist = Grid::filled(self.grid.width(), self.grid.height(), None);
+        dist[start] = Some(0);
+        let mut queue = VecDeque::from([(start, 0)]);
+
+        while let Some((p, d)) = queue.pop_front() {
+            for n in self.next(p) {
+                if dist[n].is_none() {
+                    dist[n] = Some(d + 1);
+                    queue.push_back((n, d + 1));
+                }
+            }
+        }
+
+        dist
+    }
+
+    // For every cell, how many different ways there are to walk from it to
+    // somewhere `is_end`, stopping at the first end. Each cell is only worked
+    // out once, but the steps mustn't lead round in a loop, and any that do
+    // are counted as going nowhere.
+    pub fn path_counts(&self, is_end: impl Fn(TextPoint) -> bool) -> Grid<usize> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            New,
+            Open,
+            Done,
+        }
+
+        let mut counts = Grid::filled(self.grid.width(), self.grid.height(), 0);
+        let mut state = Grid::filled(self.grid.width(), self.grid.height(), State::New);
+
+        for start in self.grid.points() {
+            // Children go on top of their parent, so they're finished first
+            let mut stack = vec![(start, false)];
+            while let Some((p, children_done)) = stack.pop() {
+                if children_done {
+                    counts[p] = if is_end(p) {
+                        1
+                    } else {
+                        self.next(p).map(|n| counts[n]).sum()
+                    };
+                    state[p] = State::Done;
+                } else if state[p] == State::New {
+                    state[p] = State::Open;
+                    stack.push((p, true));
+                    if !is_end(p) {
+                        stack.extend(
+                            self.next(p)
+                                .filter(|n| state[*n] == State::New)
+                                .map(|n| (n, false)),
+                        );
+                    }
+                }
+            }
+        }
+
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_point(row: usize, col: usize) -> TextPoint {
+        TextPoint { row, col }
+    }
+
+    fn climb(from: &u8, to: &u8) -> bool {
+        *to == from + 1
+    }
+
+    #[test]
+    fn test_reachable() {
+        let g = Grid::parse("0123\n1x34\n2345").unwrap();
+        let w = Walker::new(&g, climb);
+
+        assert_eq!(w.reachable(text_point(0, 0)).len(), 11);
+        assert_eq!(
+            w.reachable(text_point(1, 3)),
+            HashSet::from([text_point(1, 3), text_point(2, 3)])
+        );
+    }
+
+    #[test]
+    fn test_distances() {
+        let g = Grid::parse("0123\n1x34\n2345").unwrap();
+        let dist = Walker::new(&g, climb).distances(text_point(0, 0));
+
+        assert_eq!(dist[text_point(2, 3)], Some(5));
+        assert_eq!(dist[text_point(1, 1)], None);
+    }
+
+    #[test]
+    fn test_path_counts() {
+        let g = Grid::parse("0123\n1x34\n2345").unwrap();
+        let counts = Walker::new(&g, climb).path_counts(|p| g[p] == b'5');
+
+        // Four ways round the x, which nothing can climb onto
+        assert_eq!(counts[text_point(0, 0)], 4);
+        assert_eq!(counts[text_point(2, 3)], 1);
+        assert_eq!(counts[text_point(1, 1)], 0);
+    }
+
+    #[test]
+    fn test_path_counts_with_a_loop() {
+        // Walking about on the flat can go round forever, so this just has to finish
+        let g = Grid::parse("00\n00").unwrap();
+        let counts = Walker::new(&g, |a, b| a == b).path_counts(|p| p == text_point(1, 1));
+        assert_eq!(counts[text_point(1, 1)], 1);
+    }
+}
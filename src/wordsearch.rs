@@ -0,0 +1,218 @@
+use enum_iterator::all;
+
+use crate::{direction::Compass, error::Result, grid::Grid, point::TextPoint};
+
+// A word written in a straight line from `start`, heading `direction`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WordMatch<'w> {
+    pub word: &'w str,
+    pub start: TextPoint,
+    pub direction: Compass,
+}
+
+// Every place any of `words` appears, in all eight directions. A palindrome
+// shows up twice in the same cells, once each way.
+pub fn find_words<'w>(grid: &Grid<u8>, words: &[&'w str]) -> Vec<WordMatch<'w>> {
+    let corner = grid.corner();
+    let mut found = Vec::new();
+
+    for (start, c) in grid.cells() {
+        for word in words.iter().filter(|w| w.as_bytes().first() == Some(c)) {
+            for direction in all::<Compass>() {
+                let mut p = Some(start);
+                let matched = word.bytes().all(|b| match p {
+                    Some(here) if grid[here] == b => {
+                        p = here.step(direction, corner);
+                        true
+                    }
+                    _ => false,
+                });
+                if matched {
+                    found.push(WordMatch {
+                        word,
+                        start,
+                        direction,
+                    });
+                }
+            }
+        }
+    }
+
+    found
+}
+
+// How a mask has been turned before it matched. Reflecting happens first,
+// left to right, then it's turned clockwise a quarter at a time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Orientation {
+    pub reflected: bool,
+    pub quarter_turns: u8,
+}
+
+// A 2D shape to look for, where the wildcard matches anything
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Mask(Grid<Option<u8>>);
+
+impl Mask {
+    pub fn parse(pattern: &str, wildcard: u8) -> Result<Self> {
+        let grid = Grid::parse(pattern)?;
+        Ok(Mask(grid.map(|c| (*c != wildcard).then_some(*c))))
+    }
+
+    // A quarter turn clockwise
+    pub fn rotate(&self) -> Mask {
+        Mask(self.0.rotate_right())
+    }
+
+    // Mirrored left to right
+    pub fn reflect(&self) -> Mask {
+        Mask(self.0.flip_horizontal())
+    }
+
+    // Every different way this mask can be turned over or round. Symmetrical
+    // masks have fewer than eight, so nothing gets found twice in one place.
+    pub fn orientations(&self) -> Vec<(Orientation, Mask)> {
+        let mut found: Vec<(Orientation, Mask)> = Vec::new();
+
+        for reflected in [false, true] {
+            let mut mask = if reflected {
+                self.reflect()
+            } else {
+                self.clone()
+            };
+            for quarter_turns in 0..4 {
+                if !found.iter().any(|(_, m)| *m == mask) {
+                    let orientation = Orientation {
+                        reflected,
+                        quarter_turns,
+                    };
+                    found.push((orientation, mask.clone()));
+                }
+                mask = mask.rotate();
+            }
+        }
+
+        found
+    }
+
+    fn matches_at(&self, grid: &Grid<u8>, top_left: TextPoint) -> bool {
+        self.0.cells().all(|(p, want)| {
+            let here = TextPoint {
+                row: top_left.row + p.row,
+                col: top_left.col + p.col,
+            };
+            match (want, grid.get(here)) {
+                (None, Some(_)) => true,
+                (Some(want), Some(got)) => want == got,
+                (_, None) => false,
+            }
+        })
+    }
+}
+
+// Where a mask matched, by the top left of the turned mask
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaskMatch {
+    pub top_left: TextPoint,
+    pub orientation: Orientation,
+}
+
+// Every place the mask fits, any way round
+pub fn find_mask(grid: &Grid<u8>, mask: &Mask) -> Vec<MaskMatch> {
+    let orientations = mask.orientations();
+
+    grid.points()
+        .flat_map(|top_left| {
+            orientations
+                .iter()
+                .filter(move |(_, m)| m.matches_at(grid, top_left))
+                .map(move |(orientation, _)| MaskMatch {
+                    top_left,
+                    orientation: *orientation,
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_point(row: usize, col: usize) -> TextPoint {
+        TextPoint { row, col }
+    }
+
+    #[test]
+    fn test_find_words() {
+        let g = Grid::parse("CAT\nxAx\nTxC").unwrap();
+        let found = find_words(&g, &["CAT", "AT", "DOG"]);
+
+        assert_eq!(
+            found,
+            vec![
+                WordMatch {
+                    word: "CAT",
+                    start: text_point(0, 0),
+                    direction: Compass::E
+                },
+                WordMatch {
+                    word: "AT",
+                    start: text_point(0, 1),
+                    direction: Compass::E
+                },
+                WordMatch {
+                    word: "AT",
+                    start: text_point(1, 1),
+                    direction: Compass::NE
+                },
+                WordMatch {
+                    word: "AT",
+                    start: text_point(1, 1),
+                    direction: Compass::SW
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_words_stop_at_the_edge() {
+        let g = Grid::parse("AB\nBA").unwrap();
+        assert!(find_words(&g, &["ABC"]).is_empty());
+        assert_eq!(find_words(&g, &["AB"]).len(), 4);
+    }
+
+    #[test]
+    fn test_turning_masks() {
+        let m = Mask::parse("ab\ncd\nef", b'?').unwrap();
+        assert_eq!(m.rotate(), Mask::parse("eca\nfdb", b'?').unwrap());
+        assert_eq!(m.reflect(), Mask::parse("ba\ndc\nfe", b'?').unwrap());
+        assert_eq!(m.rotate().rotate().rotate().rotate(), m);
+        assert_eq!(m.orientations().len(), 8);
+
+        // Symmetrical both ways, so turning it over or half way round does nothing
+        let m = Mask::parse("a?a\n?b?", b'?').unwrap();
+        assert_eq!(m.orientations().len(), 4);
+    }
+
+    #[test]
+    fn test_find_mask() {
+        let g = Grid::parse("ax\nxb").unwrap();
+        let m = Mask::parse("a?\n?b", b'?').unwrap();
+
+        let found = find_mask(&g, &m);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].top_left, text_point(0, 0));
+
+        let g = Grid::parse("abx\nxab\nbxa").unwrap();
+        let m = Mask::parse("ab", b'?').unwrap();
+        let found = find_mask(&g, &m);
+        assert_eq!(found.len(), 4);
+        assert!(found.contains(&MaskMatch {
+            top_left: text_point(1, 2),
+            orientation: Orientation {
+                reflected: false,
+                quarter_turns: 3
+            }
+        }));
+    }
+}